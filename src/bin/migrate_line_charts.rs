@@ -0,0 +1,36 @@
+use data_processor::{
+    chart_updater::migrate_line_chart_data,
+    charts,
+    config::Config,
+    service,
+    util::{logging::init_logging, redis::get_redis_pool},
+};
+
+/// Moves the data of all line charts to the key scheme that is used since line
+/// chart data is written in the same pipeline as the other data of a service.
+#[actix_web::main]
+async fn main() {
+    init_logging();
+
+    let config = Config::load().expect("Invalid configuration");
+    let pool = get_redis_pool(&config.redis)
+        .await
+        .expect("Failed to create Redis pool");
+    let mut con = pool.get().await.expect("Failed to get Redis connection");
+
+    let services = service::find_all(&mut con)
+        .await
+        .expect("Failed to load services");
+
+    for service in services {
+        tracing::info!(service_id = service.id, "Migrating line charts");
+        let charts = charts::find_by_ids(&mut con, service.charts)
+            .await
+            .expect("Failed to load charts");
+        for chart in charts.values().flatten().filter_map(|c| c.as_ref().ok()) {
+            migrate_line_chart_data(chart, &mut con)
+                .await
+                .expect("Failed to migrate line chart data");
+        }
+    }
+}
@@ -1,46 +1,136 @@
 use std::collections::HashMap;
 
-use redis::AsyncCommands;
+use once_cell::sync::Lazy;
+use redis::{aio::ConnectionLike, AsyncCommands};
 
 use crate::{
     charts::{
-        advanced_pie::AdvancedPie,
-        chart::ChartType,
-        drilldown_pie::DrilldownPie,
-        simple_map::SimpleMap,
-        simple_pie::SimplePie,
-        single_line_chart::{SingleLineChart, SingleLineChartFilter},
-        Chart,
+        advanced_bar::AdvancedBar, advanced_map::AdvancedMap, advanced_pie::AdvancedPie,
+        chart::ChartType, drilldown_pie::DrilldownPie, multi_line_chart::MultiLineChart,
+        simple_bar::SimpleBar, simple_map::SimpleMap, simple_pie::SimplePie,
+        single_line_chart::SingleLineChart, Chart,
     },
     date_util::tms2000_to_timestamp,
+    metrics,
     submit_data_schema::SubmitDataChartSchema,
+    util::geo_ip::ISO_COUNTRIES,
 };
 
-pub async fn update_chart<C: AsyncCommands>(
+/// Lua script that adds a value to a line of a line chart.
+///
+/// The script is invoked with `EVALSHA` (see [`execute_chart_pipeline`]).
+///
+/// Line charts can contain arbitrary large values, so the sum is saturated at
+/// the min/max value instead of failing the whole pipeline on an overflow.
+///
+/// For multi line charts, the set with the known lines is passed as second key.
+/// The value is then only added if the line is already known or the chart has
+/// not reached its line limit yet. Using a script allows us to check the limit
+/// without an additional round trip.
+const LINE_CHART_SCRIPT: &str = r"
+if #KEYS > 1 and redis.call('SISMEMBER', KEYS[2], ARGV[4]) == 0 then
+    if redis.call('SCARD', KEYS[2]) >= tonumber(ARGV[5]) then
+        return 0
+    end
+    redis.call('SADD', KEYS[2], ARGV[4])
+end
+local result = redis.pcall('HINCRBY', KEYS[1], ARGV[1], ARGV[2])
+if type(result) == 'table' and result.err then
+    redis.call('HSET', KEYS[1], ARGV[1], ARGV[3])
+end
+return 1
+";
+
+static LINE_CHART_SCRIPT_SHA: Lazy<String> =
+    Lazy::new(|| redis::Script::new(LINE_CHART_SCRIPT).get_hash().to_string());
+
+/// The outcome of [`update_chart`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChartUpdate {
+    /// The data was added to the pipeline.
+    Updated,
+    /// The data was blocked by the filter of the chart.
+    Filtered,
+}
+
+#[tracing::instrument(
+    level = "debug",
+    skip_all,
+    fields(chart_id = %data.chart_id, chart_type = ?chart.r#type)
+)]
+pub fn update_chart(
     chart: &Chart,
     data: &SubmitDataChartSchema,
     tms2000: i64,
     country_iso: Option<&str>,
-    // Used where possible (currently not possible for line charts)
     pipeline: &mut redis::Pipeline,
-    con: &mut C,
-) -> Result<(), serde_json::Error> {
+) -> Result<ChartUpdate, serde_json::Error> {
+    let result = add_chart_data(chart, data, tms2000, country_iso, pipeline);
+    let label = match result {
+        Ok(ChartUpdate::Updated) => "updated",
+        Ok(ChartUpdate::Filtered) => "filtered",
+        Err(_) => "parse_error",
+    };
+    metrics::CHART_UPDATES
+        .with_label_values(&[chart.r#type.as_str(), label])
+        .inc();
+    result
+}
+
+fn add_chart_data(
+    chart: &Chart,
+    data: &SubmitDataChartSchema,
+    tms2000: i64,
+    country_iso: Option<&str>,
+    pipeline: &mut redis::Pipeline,
+) -> Result<ChartUpdate, serde_json::Error> {
     match chart.r#type {
         ChartType::SingleLineChart => {
             let data: SingleLineChart = serde_json::from_value(data.data.clone())?;
-            let should_block = match chart.data.get("filter") {
-                Some(filter) => {
-                    match serde_json::from_value::<SingleLineChartFilter>(filter.clone()) {
-                        Ok(filter) => filter.should_block(&data),
-                        Err(_) => false,
+            let should_block = chart
+                .data
+                .line_filter()
+                .map(|filter| filter.should_block(&data))
+                .unwrap_or(false);
+            if should_block {
+                tracing::debug!("Filtered line chart value");
+                return Ok(ChartUpdate::Filtered);
+            }
+            update_line_chart_data(
+                chart.service_id,
+                chart.id,
+                tms2000,
+                "1",
+                data.value,
+                pipeline,
+            );
+        }
+        ChartType::MultiLineChart => {
+            let data: MultiLineChart = serde_json::from_value(data.data.clone())?;
+            let filter = chart.data.line_filter();
+            let max_lines = chart.data.max_lines();
+            let mut filtered_lines = 0;
+            for (line, value) in data.values.iter() {
+                if let Some(filter) = filter {
+                    if filter.should_block_value(*value) {
+                        filtered_lines += 1;
+                        continue;
                     }
                 }
-                None => false,
-            };
-            if should_block {
-                return Ok(());
+                update_multi_line_chart_data(
+                    chart.service_id,
+                    chart.id,
+                    tms2000,
+                    line,
+                    *value,
+                    max_lines,
+                    pipeline,
+                );
+            }
+            if filtered_lines > 0 && filtered_lines == data.values.len() {
+                tracing::debug!("Filtered all line chart values");
+                return Ok(ChartUpdate::Filtered);
             }
-            update_line_chart_data(chart.id, tms2000, "1", data.value, con).await;
         }
         ChartType::SimplePie => {
             let data: SimplePie = serde_json::from_value(data.data.clone())?;
@@ -89,7 +179,7 @@ pub async fn update_chart<C: AsyncCommands>(
                     if let Some(country_iso) = country_iso {
                         country_iso
                     } else {
-                        return Ok(());
+                        return Ok(ChartUpdate::Updated);
                     }
                 } else {
                     &data.value
@@ -99,16 +189,58 @@ pub async fn update_chart<C: AsyncCommands>(
             );
         }
         ChartType::AdvancedMap => {
-            // TODO Currently not supported
+            let data: AdvancedMap = serde_json::from_value(data.data.clone())?;
+            for (value_name, value) in data.values.iter() {
+                let country_iso = if value_name == "AUTO" {
+                    match country_iso {
+                        Some(country_iso) => country_iso,
+                        None => continue,
+                    }
+                } else {
+                    value_name.as_str()
+                };
+                if !ISO_COUNTRIES.contains_key(country_iso) {
+                    tracing::debug!(country_iso, "Skipped unknown country code");
+                    continue;
+                }
+                update_map_data(
+                    chart.service_id,
+                    chart.id,
+                    tms2000,
+                    country_iso,
+                    *value,
+                    pipeline,
+                );
+            }
         }
         ChartType::SimpleBar => {
-            // TODO Currently not supported
+            let data: SimpleBar = serde_json::from_value(data.data.clone())?;
+            for (bar_name, value) in data.values.iter() {
+                update_bar_data(
+                    chart.service_id,
+                    chart.id,
+                    tms2000,
+                    bar_name,
+                    &[value.value()],
+                    pipeline,
+                );
+            }
         }
         ChartType::AdvancedBar => {
-            // TODO Currently not supported
+            let data: AdvancedBar = serde_json::from_value(data.data.clone())?;
+            for (bar_name, values) in data.values.iter() {
+                update_bar_data(
+                    chart.service_id,
+                    chart.id,
+                    tms2000,
+                    bar_name,
+                    values,
+                    pipeline,
+                );
+            }
         }
     }
-    Ok(())
+    Ok(ChartUpdate::Updated)
 }
 
 pub fn update_pie_data(
@@ -136,22 +268,212 @@ pub fn update_map_data(
     update_pie_data(service_id, chart_id, tms2000, value_name, value, pipeline);
 }
 
-pub async fn update_line_chart_data<C: AsyncCommands>(
+/// The key of the hash that contains the data of a line of a line chart.
+///
+/// The key uses the service id as hash tag, so that it ends up in the same slot
+/// as all other data of the service and can be written in the same pipeline.
+pub fn line_chart_key(service_id: u32, chart_id: u64, line: &str) -> String {
+    format!("data:{{{}}}.{}.line.{}", service_id, chart_id, line)
+}
+
+/// The key of the set that contains the known lines of a multi line chart.
+pub fn line_names_key(service_id: u32, chart_id: u64) -> String {
+    format!("lines:{{{}}}.{}", service_id, chart_id)
+}
+
+pub fn update_line_chart_data(
+    service_id: u32,
     chart_id: u64,
     tms2000: i64,
     line: &str,
-    value: i16,
-    con: &mut C,
+    value: i64,
+    pipeline: &mut redis::Pipeline,
+) {
+    pipeline
+        .cmd("EVALSHA")
+        .arg(LINE_CHART_SCRIPT_SHA.as_str())
+        .arg(1)
+        .arg(line_chart_key(service_id, chart_id, line))
+        .arg(tms2000_to_timestamp(tms2000))
+        .arg(value)
+        .arg(saturated(value))
+        .ignore();
+}
+
+/// Updates the data of a line of a multi line chart.
+///
+/// Every chart has a limit of distinct line names to prevent an unbounded
+/// number of keys. A line that was seen before is always updated, a new line is
+/// only accepted (and remembered) as long as the limit is not reached.
+pub fn update_multi_line_chart_data(
+    service_id: u32,
+    chart_id: u64,
+    tms2000: i64,
+    line: &str,
+    value: i64,
+    max_lines: usize,
+    pipeline: &mut redis::Pipeline,
 ) {
-    let key = format!("data:{{{}}}.{}", chart_id, line);
-    match con.hincr(key, tms2000_to_timestamp(tms2000), value).await {
-        Ok(()) => (),
-        Err(e) => {
-            // TODO Proper logging framework
-            eprintln!("Failed to update line chart data: {}", e);
-            ()
+    pipeline
+        .cmd("EVALSHA")
+        .arg(LINE_CHART_SCRIPT_SHA.as_str())
+        .arg(2)
+        .arg(line_chart_key(service_id, chart_id, line))
+        .arg(line_names_key(service_id, chart_id))
+        .arg(tms2000_to_timestamp(tms2000))
+        .arg(value)
+        .arg(saturated(value))
+        .arg(line)
+        .arg(max_lines)
+        .ignore();
+}
+
+/// Executes a pipeline that was filled by [`update_chart`].
+///
+/// Line charts are updated with `EVALSHA`, so the script is not sent with every
+/// submission. If Redis does not know the script (e.g. after a restart), only the
+/// script invocations failed and they are repeated with `EVAL`, which also caches the
+/// script again. The other commands of the pipeline are not repeated.
+pub async fn execute_chart_pipeline<C: ConnectionLike>(
+    pipeline: &redis::Pipeline,
+    con: &mut C,
+) -> Result<(), redis::RedisError> {
+    match pipeline.query_async::<()>(con).await {
+        Err(e) if e.kind() == redis::ErrorKind::NoScriptError => {
+            tracing::debug!("Line chart script is not cached, falling back to EVAL");
+            let mut fallback = redis::pipe();
+            for cmd in pipeline.cmd_iter().filter_map(to_eval_cmd) {
+                fallback.add_command(cmd).ignore();
+            }
+            fallback.query_async(con).await
+        }
+        result => result,
+    }
+}
+
+/// Turns an `EVALSHA` of the line chart script into an `EVAL` with the same arguments.
+fn to_eval_cmd(cmd: &redis::Cmd) -> Option<redis::Cmd> {
+    let mut args = cmd.args_iter().map(|arg| match arg {
+        redis::Arg::Simple(arg) => arg,
+        redis::Arg::Cursor => &[],
+    });
+    if args.next()? != b"EVALSHA" || args.next()? != LINE_CHART_SCRIPT_SHA.as_bytes() {
+        return None;
+    }
+    let mut eval = redis::cmd("EVAL");
+    eval.arg(LINE_CHART_SCRIPT);
+    for arg in args {
+        eval.arg(arg);
+    }
+    Some(eval)
+}
+
+/// The value a line chart sum saturates at if adding the given value overflows.
+fn saturated(value: i64) -> i64 {
+    if value < 0 {
+        i64::MIN
+    } else {
+        i64::MAX
+    }
+}
+
+/// Lua script that takes a snapshot of an old line chart key for the migration.
+///
+/// The old key is renamed to a snapshot key (in the same hash slot), so values
+/// that are written afterwards by a data processor that still uses the old key
+/// scheme end up in a new old key and are migrated by the next run. Every snapshot
+/// gets a new id. If the snapshot of a previous run was not migrated completely,
+/// it is returned instead (with `1` as second value).
+static SNAPSHOT_LINE_CHART_SCRIPT: Lazy<redis::Script> = Lazy::new(|| {
+    redis::Script::new(
+        r"
+if redis.call('EXISTS', KEYS[2]) == 1 then
+    return {redis.call('GET', KEYS[3]), 1}
+end
+if redis.call('EXISTS', KEYS[1]) == 0 then
+    return nil
+end
+redis.call('RENAME', KEYS[1], KEYS[2])
+return {tostring(redis.call('INCR', KEYS[3])), 0}
+",
+    )
+});
+
+/// Lua script that adds a snapshot of an old line chart key to the current key.
+///
+/// The snapshot is in another hash slot, so its data is read beforehand and passed
+/// as `field, value, saturated value` triples after the snapshot id. The id is
+/// remembered in a set next to the chart data, which prevents that a snapshot is
+/// added twice if it could not be deleted afterwards. The sum is saturated the same
+/// way as in [`LINE_CHART_SCRIPT`].
+static MIGRATE_LINE_CHART_SCRIPT: Lazy<redis::Script> = Lazy::new(|| {
+    redis::Script::new(
+        r"
+if redis.call('SISMEMBER', KEYS[2], ARGV[1]) == 1 then
+    return 0
+end
+redis.call('SADD', KEYS[2], ARGV[1])
+for i = 2, #ARGV, 3 do
+    local result = redis.pcall('HINCRBY', KEYS[1], ARGV[i], ARGV[i + 1])
+    if type(result) == 'table' and result.err then
+        redis.call('HSET', KEYS[1], ARGV[i], ARGV[i + 2])
+    end
+end
+return 1
+",
+    )
+});
+
+/// Moves the data of a line chart from the old key scheme to the current one.
+///
+/// Single line chart data used to be stored in `data:{chart_id}.1` hashes, which
+/// used the chart id as hash tag and thus could not be written in the same
+/// pipeline as the other data of a service. Existing values are added to data
+/// that might have already been written with the new scheme, so it is safe to run
+/// the migration while the data processor is running. Values that are written to
+/// the old key in the meantime are migrated by the next run, and an interrupted
+/// run is completed by the next one without adding any value twice.
+pub async fn migrate_line_chart_data<C: AsyncCommands>(
+    chart: &Chart,
+    con: &mut C,
+) -> Result<(), redis::RedisError> {
+    if !matches!(chart.r#type, ChartType::SingleLineChart) {
+        return Ok(());
+    }
+
+    let old_key = format!("data:{{{}}}.1", chart.id);
+    let snapshot_key = format!("migrating:{{{}}}.1", chart.id);
+    loop {
+        let snapshot: Option<(String, bool)> = SNAPSHOT_LINE_CHART_SCRIPT
+            .key(&old_key)
+            .key(&snapshot_key)
+            .key(format!("migration_id:{{{}}}.1", chart.id))
+            .invoke_async(con)
+            .await?;
+        let Some((snapshot_id, resumed)) = snapshot else {
+            break;
+        };
+
+        let old_data: HashMap<String, i64> = con.hgetall(&snapshot_key).await?;
+        let mut invocation = MIGRATE_LINE_CHART_SCRIPT.prepare_invoke();
+        invocation
+            .key(line_chart_key(chart.service_id, chart.id, "1"))
+            .key(format!("migrated:{{{}}}.{}", chart.service_id, chart.id))
+            .arg(&snapshot_id);
+        for (timestamp, value) in old_data.iter() {
+            invocation.arg(timestamp).arg(*value).arg(saturated(*value));
+        }
+        let _: () = invocation.invoke_async(con).await?;
+        let _: () = con.del(&snapshot_key).await?;
+
+        // The old key might have been written to after the snapshot of the
+        // interrupted run was taken
+        if !resumed {
+            break;
         }
     }
+
+    Ok(())
 }
 
 pub fn update_drilldown_pie_data(
@@ -162,9 +484,9 @@ pub fn update_drilldown_pie_data(
     values: HashMap<String, u16>,
     pipeline: &mut redis::Pipeline,
 ) {
-    let mut total_value = 0;
+    let mut total_value: u64 = 0;
     for (value_key, value) in values.iter() {
-        total_value += value;
+        total_value += u64::from(*value);
         let key = format!(
             "data:{{{}}}.{}.{}.{}",
             service_id, chart_id, tms2000, value_name
@@ -176,3 +498,32 @@ pub fn update_drilldown_pie_data(
     pipeline.zincr(&key, value_name, total_value);
     pipeline.expire(&key, 60 * 61);
 }
+
+/// Updates the data of a bar chart.
+///
+/// The values of each category are summed up in a hash (one per bar) with the
+/// index of the category as field. Additionally, the total of all categories is
+/// stored in a sorted set (the same way as the total of a drilldown pie) to
+/// know which bars exist.
+pub fn update_bar_data(
+    service_id: u32,
+    chart_id: u64,
+    tms2000: i64,
+    bar_name: &str,
+    values: &[u16],
+    pipeline: &mut redis::Pipeline,
+) {
+    let mut total_value: u64 = 0;
+    let key = format!(
+        "data:{{{}}}.{}.{}.{}",
+        service_id, chart_id, tms2000, bar_name
+    );
+    for (category, value) in values.iter().enumerate() {
+        total_value += u64::from(*value);
+        pipeline.hincr(&key, category, *value);
+    }
+    pipeline.expire(&key, 60 * 61);
+    let key = format!("data:{{{}}}.{}.{}", service_id, chart_id, tms2000);
+    pipeline.zincr(&key, bar_name, total_value);
+    pipeline.expire(&key, 60 * 61);
+}
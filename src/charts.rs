@@ -1,18 +1,23 @@
+pub mod advanced_bar;
+pub mod advanced_map;
 pub mod advanced_pie;
 pub mod chart;
+pub mod chart_config;
 pub mod drilldown_pie;
+pub mod multi_line_chart;
+pub mod simple_bar;
 pub mod simple_map;
 pub mod simple_pie;
 pub mod single_line_chart;
 
-use std::collections::HashMap;
+use std::{collections::HashMap, fmt};
 
 use chart::ChartType;
+use chart_config::ChartConfig;
 use redis::AsyncCommands;
-use serde::{Deserialize, Serialize};
-use serde_json::Value;
+use serde::Serialize;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize)]
 pub struct Chart {
     pub id: u64,
     pub id_custom: String,
@@ -20,15 +25,29 @@ pub struct Chart {
     pub position: u16,
     pub title: String,
     pub default: bool,
-    pub data: Value,
+    pub data: ChartConfig,
     pub service_id: u32,
 }
 
+/// A chart with an invalid `type` or `data` that can not be used.
+#[derive(Debug, Clone)]
+pub struct ChartConfigError {
+    pub id: u64,
+    pub id_custom: String,
+    pub message: String,
+}
+
+impl fmt::Display for ChartConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid config of chart {}: {}", self.id, self.message)
+    }
+}
+
 /// Find all charts with the given IDs.
 pub async fn find_by_ids<C: AsyncCommands>(
     con: &mut C,
     ids: Vec<u64>,
-) -> Result<HashMap<u64, Option<Chart>>, redis::RedisError> {
+) -> Result<HashMap<u64, Option<Result<Chart, ChartConfigError>>>, redis::RedisError> {
     // TODO: Move all charts from a single service in a single hash and use pipelining
     let mut response = HashMap::new();
     for id in ids {
@@ -37,30 +56,52 @@ pub async fn find_by_ids<C: AsyncCommands>(
     Ok(response)
 }
 
+/// Finds the chart with the given ID.
+///
+/// A chart with an invalid `type` or `data` is returned as [`ChartConfigError`], so
+/// that it can be told apart from a chart that does not exist.
 pub async fn find_by_id<C: AsyncCommands>(
     con: &mut C,
     id: u64,
-) -> Result<Option<Chart>, redis::RedisError> {
+) -> Result<Option<Result<Chart, ChartConfigError>>, redis::RedisError> {
     let map: HashMap<String, String> = con.hgetall(format!("charts:{}", id)).await?;
 
     if map.is_empty() {
         return Ok(None);
     }
 
-    Ok(Some(Chart {
+    let id_custom = map
+        .get("id")
+        .expect("Chart without 'id_custom'")
+        .to_string();
+    let config_error = |message: String| {
+        tracing::warn!(chart_id = id, error = %message, "Chart with invalid config");
+        Ok(Some(Err(ChartConfigError {
+            id,
+            id_custom: id_custom.clone(),
+            message,
+        })))
+    };
+
+    let r#type: ChartType = match serde_json::from_str(&format!(
+        "\"{}\"",
+        map.get("type").expect("Chart without 'type'")
+    )) {
+        Ok(t) => t,
+        Err(e) => return config_error(format!("unknown 'type': {}", e)),
+    };
+
+    let data = match serde_json::from_str(map.get("data").expect("Chart without 'data'"))
+        .and_then(|data| ChartConfig::from_value(&r#type, data))
+    {
+        Ok(data) => data,
+        Err(e) => return config_error(format!("invalid 'data': {}", e)),
+    };
+
+    Ok(Some(Ok(Chart {
         id,
-        id_custom: map
-            .get("id")
-            .expect("Chart without 'id_custom'")
-            .to_string(),
-        r#type: match serde_json::from_str(&format!(
-            "\"{}\"",
-            map.get("type").expect("Chart without 'type'")
-        )) {
-            Ok(t) => t,
-            // TODO Log warning
-            Err(_) => return Ok(None),
-        },
+        id_custom,
+        r#type,
         position: map
             .get("position")
             .expect("Chart without 'position'")
@@ -68,14 +109,11 @@ pub async fn find_by_id<C: AsyncCommands>(
             .expect("Chart with non-numeric or to small/large 'position"),
         title: map.get("title").expect("Chart without 'title'").to_string(),
         default: map.get("default").unwrap_or(&String::from("0")) == "1",
-        data: match serde_json::from_str(map.get("data").expect("Chart without 'data'")) {
-            Ok(d) => d,
-            Err(_) => Value::Null,
-        },
+        data,
         service_id: map
             .get("pluginId")
             .expect("Chart without 'pluginId'")
             .parse()
             .expect("Chart with non-numeric 'pluginId"),
-    }))
+    })))
 }
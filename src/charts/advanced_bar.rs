@@ -0,0 +1,10 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+#[derive(Debug, Validate, Deserialize, Serialize)]
+pub struct AdvancedBar {
+    /// The values of each bar. The position in the array is the category.
+    pub values: HashMap<String, Vec<u16>>,
+}
@@ -0,0 +1,9 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+#[derive(Debug, Validate, Deserialize, Serialize)]
+pub struct AdvancedMap {
+    pub values: HashMap<String, u16>,
+}
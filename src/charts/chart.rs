@@ -1,9 +1,16 @@
+use std::sync::Arc;
+
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use serde_with::skip_serializing_none;
 
+use super::chart_config::ChartConfig;
+use crate::parser::{self, request_parser_config::RequestParserConfig, Parser};
+
 /// The definition of a concrete chart
 #[skip_serializing_none]
 #[derive(Serialize, Deserialize, Debug)]
+#[serde(try_from = "RawChart")]
 pub struct Chart {
     /// The unique id of the chart
     pub id: i32,
@@ -21,26 +28,15 @@ pub struct Chart {
     /// The ID of the service this chart belongs to
     #[serde(rename = "pluginId")]
     pub service_id: i32,
-    /// Additional data depending on the chart type.
-    ///
-    /// For example, for a line chart, it might look like this:
-    /// ```json
-    /// "lineName": "Players",
-    ///  "filter": {
-    ///    "enabled": true,
-    ///    "maxValue": 200,
-    ///    "minValue": 0
-    ///  }
-    /// ```
-    // TODO Better typing or at least document what the data could look like
-    //  for each chart type.
-    pub data: serde_json::Value,
+    /// Additional data depending on the chart type (see [`ChartConfig`]).
+    pub data: ChartConfig,
 }
 
 /// A template for a default chart (i.e. how a chart for a newly created service
 /// should look like).
 #[skip_serializing_none]
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(try_from = "RawDefaultChartTemplate")]
 pub struct DefaultChartTemplate {
     /// A human-readable id of the chart. Only unique within the service.
     ///
@@ -49,23 +45,94 @@ pub struct DefaultChartTemplate {
     #[serde(rename = "type")]
     pub chart_type: ChartType,
     pub title: String,
-    /// Additional data depending on the chart type.
-    ///
-    /// For example, for a line chart, it might look like this:
-    /// ```json
-    /// "lineName": "Players",
-    ///  "filter": {
-    ///    "enabled": true,
-    ///    "maxValue": 200,
-    ///    "minValue": 0
-    ///  }
-    /// ```
-    // TODO Better typing or at least document what the data could look like
-    //  for each chart type.
-    pub data: serde_json::Value,
+    /// Additional data depending on the chart type (see [`ChartConfig`]).
+    pub data: ChartConfig,
 
     #[serde(rename = "requestParser")]
-    pub request_parser: serde_json::Value,
+    pub request_parser: RequestParserConfig,
+
+    /// The parser for the `request_parser`, created once when the template is loaded.
+    #[serde(skip)]
+    parser: Arc<dyn Parser>,
+}
+
+impl DefaultChartTemplate {
+    /// Creates a template. Fails if the parser for the `request_parser` cannot be created.
+    pub fn new(
+        id: String,
+        chart_type: ChartType,
+        title: String,
+        data: ChartConfig,
+        request_parser: RequestParserConfig,
+    ) -> Result<Self, String> {
+        let parser = parser::create_parser(&request_parser)?;
+        Ok(DefaultChartTemplate {
+            id,
+            chart_type,
+            title,
+            data,
+            request_parser,
+            parser,
+        })
+    }
+
+    pub fn parser(&self) -> &dyn Parser {
+        self.parser.as_ref()
+    }
+}
+
+/// A [`Chart`] with untyped data. The data can only be parsed once the type is known.
+#[derive(Deserialize)]
+struct RawChart {
+    id: i32,
+    id_custom: String,
+    #[serde(rename = "type")]
+    chart_type: ChartType,
+    position: i32,
+    title: String,
+    is_default: bool,
+    #[serde(rename = "pluginId")]
+    service_id: i32,
+    data: Value,
+}
+
+impl TryFrom<RawChart> for Chart {
+    type Error = serde_json::Error;
+
+    fn try_from(raw: RawChart) -> Result<Self, Self::Error> {
+        Ok(Chart {
+            data: ChartConfig::from_value(&raw.chart_type, raw.data)?,
+            id: raw.id,
+            id_custom: raw.id_custom,
+            chart_type: raw.chart_type,
+            position: raw.position,
+            title: raw.title,
+            is_default: raw.is_default,
+            service_id: raw.service_id,
+        })
+    }
+}
+
+/// A [`DefaultChartTemplate`] with untyped data.
+#[derive(Deserialize)]
+struct RawDefaultChartTemplate {
+    id: String,
+    #[serde(rename = "type")]
+    chart_type: ChartType,
+    title: String,
+    data: Value,
+    #[serde(rename = "requestParser")]
+    request_parser: RequestParserConfig,
+}
+
+impl TryFrom<RawDefaultChartTemplate> for DefaultChartTemplate {
+    type Error = serde_json::Error;
+
+    fn try_from(raw: RawDefaultChartTemplate) -> Result<Self, Self::Error> {
+        let data = ChartConfig::from_value(&raw.chart_type, raw.data)?;
+        DefaultChartTemplate::new(raw.id, raw.chart_type, raw.title, data, raw.request_parser)
+            .map_err(serde::de::Error::custom)
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -73,6 +140,9 @@ pub enum ChartType {
     #[serde(rename = "single_linechart")]
     SingleLineChart,
 
+    #[serde(rename = "multi_linechart")]
+    MultiLineChart,
+
     #[serde(rename = "simple_pie")]
     SimplePie,
 
@@ -94,3 +164,60 @@ pub enum ChartType {
     #[serde(rename = "advanced_bar")]
     AdvancedBar,
 }
+
+impl ChartType {
+    /// The name of the chart type as it is stored in Redis, e.g. `simple_pie`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChartType::SingleLineChart => "single_linechart",
+            ChartType::MultiLineChart => "multi_linechart",
+            ChartType::SimplePie => "simple_pie",
+            ChartType::AdvancedPie => "advanced_pie",
+            ChartType::DrilldownPie => "drilldown_pie",
+            ChartType::SimpleMap => "simple_map",
+            ChartType::AdvancedMap => "advanced_map",
+            ChartType::SimpleBar => "simple_bar",
+            ChartType::AdvancedBar => "advanced_bar",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::{parser::ParseContext, submit_data_schema::SubmitDataSchema};
+
+    #[test]
+    fn test_deserialize_default_chart_template() {
+        let template: DefaultChartTemplate = serde_json::from_value(json!({
+            "id": "servers",
+            "type": "single_linechart",
+            "title": "Servers",
+            "data": { "lineName": "Servers" },
+            "requestParser": { "predefinedValue": 1 }
+        }))
+        .unwrap();
+
+        let schema: SubmitDataSchema = serde_json::from_value(json!({
+            "service": { "id": 1 },
+            "serverUUID": "00000000-0000-0000-0000-000000000000"
+        }))
+        .unwrap();
+        assert_eq!(
+            template.parser().parse(&ParseContext::new(&schema)),
+            Some(json!(1))
+        );
+
+        // The options of hardcoded parsers are checked when the template is loaded
+        let result = serde_json::from_value::<DefaultChartTemplate>(json!({
+            "id": "platformVersion",
+            "type": "simple_pie",
+            "title": "Platform version",
+            "data": {},
+            "requestParser": { "useHardcodedParser": "platformVersion" }
+        }));
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,177 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use serde_with::skip_serializing_none;
+
+use super::{
+    chart::ChartType, multi_line_chart::DEFAULT_MAX_LINES, single_line_chart::SingleLineChartFilter,
+};
+
+/// The configuration of a chart (stored in its `data` property).
+///
+/// What the configuration looks like depends on the type of the chart, so it
+/// has to be parsed with [`ChartConfig::from_value`].
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+pub enum ChartConfig {
+    SingleLineChart(LineChartConfig),
+    MultiLineChart(MultiLineChartConfig),
+    SimplePie(PieChartConfig),
+    AdvancedPie(PieChartConfig),
+    DrilldownPie(PieChartConfig),
+    SimpleMap(MapChartConfig),
+    AdvancedMap(MapChartConfig),
+    SimpleBar(BarChartConfig),
+    AdvancedBar(BarChartConfig),
+}
+
+/// The configuration of a single line chart.
+///
+/// For example:
+/// ```json
+/// {
+///   "lineName": "Players",
+///   "filter": {
+///     "enabled": true,
+///     "maxValue": 200,
+///     "minValue": 0
+///   }
+/// }
+/// ```
+#[skip_serializing_none]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LineChartConfig {
+    #[serde(rename = "lineName")]
+    pub line_name: Option<String>,
+    pub filter: Option<SingleLineChartFilter>,
+}
+
+/// The configuration of a multi line chart.
+///
+/// The filter is applied to every line individually.
+#[skip_serializing_none]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MultiLineChartConfig {
+    pub filter: Option<SingleLineChartFilter>,
+    /// The maximum number of distinct lines. Defaults to [`DEFAULT_MAX_LINES`].
+    #[serde(rename = "maxLines")]
+    pub max_lines: Option<usize>,
+}
+
+/// The configuration of a simple, advanced or drilldown pie.
+#[skip_serializing_none]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PieChartConfig {
+    pub filter: Option<ValueFilter>,
+}
+
+/// The configuration of a simple or advanced map.
+#[skip_serializing_none]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MapChartConfig {
+    #[serde(rename = "valueName")]
+    pub value_name: Option<String>,
+    pub filter: Option<ValueFilter>,
+}
+
+/// The configuration of a simple or advanced bar chart.
+#[skip_serializing_none]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BarChartConfig {
+    #[serde(rename = "valueName")]
+    pub value_name: Option<String>,
+}
+
+/// A filter for the values of pies and maps.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ValueFilter {
+    pub enabled: bool,
+    #[serde(rename = "useRegex")]
+    pub use_regex: bool,
+    /// Whether the filter is a blacklist or a whitelist.
+    pub blacklist: bool,
+    pub filter: Vec<String>,
+}
+
+impl ChartConfig {
+    /// Parses the configuration of a chart with the given type.
+    ///
+    /// A missing configuration (`null`) is treated like an empty one.
+    pub fn from_value(chart_type: &ChartType, value: Value) -> Result<Self, serde_json::Error> {
+        let value = match value {
+            Value::Null => Value::Object(Default::default()),
+            value => value,
+        };
+        Ok(match chart_type {
+            ChartType::SingleLineChart => {
+                ChartConfig::SingleLineChart(serde_json::from_value(value)?)
+            }
+            ChartType::MultiLineChart => {
+                ChartConfig::MultiLineChart(serde_json::from_value(value)?)
+            }
+            ChartType::SimplePie => ChartConfig::SimplePie(serde_json::from_value(value)?),
+            ChartType::AdvancedPie => ChartConfig::AdvancedPie(serde_json::from_value(value)?),
+            ChartType::DrilldownPie => ChartConfig::DrilldownPie(serde_json::from_value(value)?),
+            ChartType::SimpleMap => ChartConfig::SimpleMap(serde_json::from_value(value)?),
+            ChartType::AdvancedMap => ChartConfig::AdvancedMap(serde_json::from_value(value)?),
+            ChartType::SimpleBar => ChartConfig::SimpleBar(serde_json::from_value(value)?),
+            ChartType::AdvancedBar => ChartConfig::AdvancedBar(serde_json::from_value(value)?),
+        })
+    }
+
+    /// The filter for the values of a single or multi line chart.
+    pub fn line_filter(&self) -> Option<&SingleLineChartFilter> {
+        match self {
+            ChartConfig::SingleLineChart(config) => config.filter.as_ref(),
+            ChartConfig::MultiLineChart(config) => config.filter.as_ref(),
+            _ => None,
+        }
+    }
+
+    /// The maximum number of distinct lines of a multi line chart.
+    pub fn max_lines(&self) -> usize {
+        match self {
+            ChartConfig::MultiLineChart(config) => config.max_lines.unwrap_or(DEFAULT_MAX_LINES),
+            _ => DEFAULT_MAX_LINES,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_from_value() {
+        let config = ChartConfig::from_value(
+            &ChartType::SingleLineChart,
+            json!({
+                "lineName": "Players",
+                "filter": {
+                    "enabled": true,
+                    "maxValue": 200,
+                    "minValue": 0
+                }
+            }),
+        )
+        .unwrap();
+        assert_eq!(config.line_filter().unwrap().max_value, Some(200));
+
+        let config = ChartConfig::from_value(&ChartType::MultiLineChart, Value::Null).unwrap();
+        assert_eq!(config.max_lines(), DEFAULT_MAX_LINES);
+
+        // Invalid configurations must not be silently ignored
+        assert!(ChartConfig::from_value(
+            &ChartType::SingleLineChart,
+            json!({ "filter": { "maxValue": "200" } })
+        )
+        .is_err());
+    }
+}
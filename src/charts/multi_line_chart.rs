@@ -0,0 +1,14 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+/// The default maximum number of distinct lines of a multi line chart.
+///
+/// Can be overridden with the `maxLines` property of the chart's data.
+pub const DEFAULT_MAX_LINES: usize = 10;
+
+#[derive(Debug, Validate, Deserialize, Serialize)]
+pub struct MultiLineChart {
+    pub values: HashMap<String, i64>,
+}
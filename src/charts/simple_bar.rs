@@ -0,0 +1,29 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+#[derive(Debug, Validate, Deserialize, Serialize)]
+pub struct SimpleBar {
+    pub values: HashMap<String, SimpleBarValue>,
+}
+
+/// The value of a single bar.
+///
+/// The bStats Metrics classes wrap the value in an array with a single element,
+/// so we accept both representations.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum SimpleBarValue {
+    Single(u16),
+    Wrapped([u16; 1]),
+}
+
+impl SimpleBarValue {
+    pub fn value(&self) -> u16 {
+        match self {
+            SimpleBarValue::Single(value) => *value,
+            SimpleBarValue::Wrapped([value]) => *value,
+        }
+    }
+}
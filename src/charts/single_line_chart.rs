@@ -3,10 +3,10 @@ use validator::Validate;
 
 #[derive(Debug, Validate, Deserialize, Serialize)]
 pub struct SingleLineChart {
-    pub value: i16,
+    pub value: i64,
 }
 
-#[derive(Debug, Validate, Deserialize, Serialize)]
+#[derive(Debug, Clone, Validate, Deserialize, Serialize)]
 pub struct SingleLineChartFilter {
     pub enabled: bool,
     #[serde(rename = "maxValue")]
@@ -17,14 +17,18 @@ pub struct SingleLineChartFilter {
 
 impl SingleLineChartFilter {
     pub fn should_block(&self, data: &SingleLineChart) -> bool {
+        self.should_block_value(data.value)
+    }
+
+    pub fn should_block_value(&self, value: i64) -> bool {
         if self.enabled {
             if let Some(max_value) = self.max_value {
-                if i64::from(data.value) > max_value {
+                if value > max_value {
                     return true;
                 }
             }
             if let Some(min_value) = self.min_value {
-                if i64::from(data.value) < min_value {
+                if value < min_value {
                     return true;
                 }
             }
@@ -0,0 +1,407 @@
+use std::{path::Path, path::PathBuf, str::FromStr};
+
+use redis::IntoConnectionInfo;
+use serde::Deserialize;
+
+use crate::util::geo_ip;
+
+/// The configuration of the data processor.
+///
+/// It is loaded once at startup with [`Config::load`] from an optional TOML file
+/// (`CONFIG_PATH`) and the environment. Environment variables take precedence over
+/// the file.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// `HOST`
+    pub host: String,
+    /// `PORT`
+    pub port: u16,
+    /// `WORKERS`, the number of physical CPU cores by default.
+    pub workers: Option<usize>,
+    pub redis: RedisConfig,
+    /// `GEOIP_DATABASE_PATH`
+    pub geoip_database_path: PathBuf,
+    /// `BEHIND_PROXY`, uses `forwarded` and `x-forwarded-for` for ip resolution.
+    pub behind_proxy: bool,
+    /// `BEHIND_CLOUDFLARE_PROXY`, uses `cf-connecting-ip` for ip resolution.
+    pub behind_cloudflare_proxy: bool,
+    /// `WORD_BLOCKLIST` (as a JSON array). Submissions that contain one of the words
+    /// are silently dropped.
+    pub word_blocklist: Vec<String>,
+    pub server_software_rules: ServerSoftwareRulesConfig,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RedisConfig {
+    /// `REDIS_MODE`
+    pub mode: RedisMode,
+    /// `REDIS_CLUSTER__URLS` (comma-separated), used in cluster mode.
+    pub cluster_urls: Vec<String>,
+    /// `REDIS_URL`, used in standalone mode.
+    pub url: Option<String>,
+    /// `REDIS_SENTINEL__URLS` (comma-separated), used in sentinel mode.
+    pub sentinel_urls: Vec<String>,
+    /// `REDIS_SENTINEL__MASTER_NAME`, used in sentinel mode.
+    pub sentinel_master_name: Option<String>,
+}
+
+/// How the data processor connects to Redis.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RedisMode {
+    /// A Redis cluster.
+    #[default]
+    Cluster,
+    /// A single Redis node.
+    Standalone,
+    /// The primary of a deployment that is managed by Redis Sentinel.
+    Sentinel,
+}
+
+impl FromStr for RedisMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "cluster" => Ok(RedisMode::Cluster),
+            "standalone" => Ok(RedisMode::Standalone),
+            "sentinel" => Ok(RedisMode::Sentinel),
+            _ => Err(String::from(
+                "expected 'cluster', 'standalone' or 'sentinel'",
+            )),
+        }
+    }
+}
+
+/// Where the server software rules are loaded from, see
+/// [`RulesSource`](crate::parser::server_software_rules::RulesSource).
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerSoftwareRulesConfig {
+    /// `SERVER_SOFTWARE_RULES_PATH`
+    pub path: Option<PathBuf>,
+    /// `SERVER_SOFTWARE_RULES_REDIS_KEY`
+    pub redis_key: Option<String>,
+    /// `SERVER_SOFTWARE_RULES_RELOAD_INTERVAL` in seconds.
+    pub reload_interval: u64,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            host: String::from("0.0.0.0"),
+            port: 8080,
+            workers: None,
+            redis: RedisConfig::default(),
+            geoip_database_path: PathBuf::from(geo_ip::DEFAULT_DATABASE_PATH),
+            behind_proxy: false,
+            behind_cloudflare_proxy: false,
+            word_blocklist: Vec::new(),
+            server_software_rules: ServerSoftwareRulesConfig::default(),
+        }
+    }
+}
+
+impl Default for ServerSoftwareRulesConfig {
+    fn default() -> Self {
+        Self {
+            path: None,
+            redis_key: None,
+            reload_interval: 60,
+        }
+    }
+}
+
+impl Config {
+    /// Loads the config file from `CONFIG_PATH` (if set), applies the environment
+    /// variables and validates the result.
+    pub fn load() -> Result<Self, String> {
+        let mut config = match std::env::var("CONFIG_PATH") {
+            Ok(path) => Self::from_file(Path::new(&path))?,
+            Err(_) => Self::default(),
+        };
+        config.apply_env(|name| std::env::var(name).ok())?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn from_file(path: &Path) -> Result<Self, String> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read config file {}: {}", path.display(), e))?;
+        toml::from_str(&content)
+            .map_err(|e| format!("Invalid config file {}: {}", path.display(), e))
+    }
+
+    /// Overrides the values with the environment variables that are set.
+    fn apply_env(&mut self, var: impl Fn(&str) -> Option<String>) -> Result<(), String> {
+        if let Some(host) = var("HOST") {
+            self.host = host;
+        }
+        if let Some(port) = var("PORT") {
+            self.port = parse_var("PORT", &port)?;
+        }
+        if let Some(workers) = var("WORKERS") {
+            self.workers = Some(parse_var("WORKERS", &workers)?);
+        }
+        if let Some(mode) = var("REDIS_MODE") {
+            self.redis.mode = parse_var("REDIS_MODE", &mode)?;
+        }
+        if let Some(urls) = var("REDIS_CLUSTER__URLS") {
+            self.redis.cluster_urls = urls
+                .split(',')
+                .map(str::trim)
+                .filter(|url| !url.is_empty())
+                .map(String::from)
+                .collect();
+        }
+        if let Some(url) = var("REDIS_URL") {
+            self.redis.url = Some(url);
+        }
+        if let Some(urls) = var("REDIS_SENTINEL__URLS") {
+            self.redis.sentinel_urls = urls
+                .split(',')
+                .map(str::trim)
+                .filter(|url| !url.is_empty())
+                .map(String::from)
+                .collect();
+        }
+        if let Some(master_name) = var("REDIS_SENTINEL__MASTER_NAME") {
+            self.redis.sentinel_master_name = Some(master_name);
+        }
+        if let Some(path) = var("GEOIP_DATABASE_PATH") {
+            self.geoip_database_path = PathBuf::from(path);
+        }
+        if let Some(behind_proxy) = var("BEHIND_PROXY") {
+            self.behind_proxy = parse_var("BEHIND_PROXY", &behind_proxy)?;
+        }
+        if let Some(behind_cloudflare_proxy) = var("BEHIND_CLOUDFLARE_PROXY") {
+            self.behind_cloudflare_proxy =
+                parse_var("BEHIND_CLOUDFLARE_PROXY", &behind_cloudflare_proxy)?;
+        }
+        if let Some(word_blocklist) = var("WORD_BLOCKLIST") {
+            self.word_blocklist = serde_json::from_str(&word_blocklist)
+                .map_err(|e| format!("WORD_BLOCKLIST must be a JSON array of strings: {}", e))?;
+        }
+        if let Some(path) = var("SERVER_SOFTWARE_RULES_PATH") {
+            self.server_software_rules.path = Some(PathBuf::from(path));
+        }
+        if let Some(key) = var("SERVER_SOFTWARE_RULES_REDIS_KEY") {
+            self.server_software_rules.redis_key = Some(key);
+        }
+        if let Some(reload_interval) = var("SERVER_SOFTWARE_RULES_RELOAD_INTERVAL") {
+            self.server_software_rules.reload_interval =
+                parse_var("SERVER_SOFTWARE_RULES_RELOAD_INTERVAL", &reload_interval)?;
+        }
+        Ok(())
+    }
+
+    /// Checks values that can be parsed but are not usable.
+    pub fn validate(&self) -> Result<(), String> {
+        self.redis.validate()?;
+        if self.workers == Some(0) {
+            return Err(String::from("WORKERS must be greater than 0"));
+        }
+        if self.server_software_rules.reload_interval == 0 {
+            return Err(String::from(
+                "SERVER_SOFTWARE_RULES_RELOAD_INTERVAL must be greater than 0",
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl RedisConfig {
+    fn validate(&self) -> Result<(), String> {
+        let (name, urls) = match self.mode {
+            RedisMode::Cluster => ("REDIS_CLUSTER__URLS", self.cluster_urls.as_slice()),
+            RedisMode::Standalone => ("REDIS_URL", self.url.as_slice()),
+            RedisMode::Sentinel => {
+                if self.sentinel_master_name.is_none() {
+                    return Err(String::from(
+                        "REDIS_SENTINEL__MASTER_NAME must be set in sentinel mode",
+                    ));
+                }
+                ("REDIS_SENTINEL__URLS", self.sentinel_urls.as_slice())
+            }
+        };
+        if urls.is_empty() {
+            return Err(format!("{} must contain at least one Redis URL", name));
+        }
+        for url in urls {
+            url.as_str()
+                .into_connection_info()
+                .map_err(|e| format!("Invalid Redis URL '{}' in {}: {}", url, name, e))?;
+        }
+        Ok(())
+    }
+}
+
+fn parse_var<T>(name: &str, value: &str) -> Result<T, String>
+where
+    T: FromStr,
+    T::Err: std::fmt::Display,
+{
+    value
+        .parse()
+        .map_err(|e| format!("Invalid value '{}' for {}: {}", value, name, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    fn apply_env(config: &mut Config, vars: &[(&str, &str)]) -> Result<(), String> {
+        let vars: HashMap<&str, &str> = vars.iter().copied().collect();
+        config.apply_env(|name| vars.get(name).map(|v| v.to_string()))
+    }
+
+    #[test]
+    fn test_apply_env() {
+        let mut config = Config::default();
+        apply_env(
+            &mut config,
+            &[
+                ("PORT", "1234"),
+                ("WORKERS", "4"),
+                ("REDIS_CLUSTER__URLS", "redis://a:6379, redis://b:6379,"),
+                ("BEHIND_PROXY", "true"),
+                ("WORD_BLOCKLIST", r#"["foo", "bar"]"#),
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(config.host, "0.0.0.0");
+        assert_eq!(config.port, 1234);
+        assert_eq!(config.workers, Some(4));
+        assert_eq!(
+            config.redis.cluster_urls,
+            vec!["redis://a:6379", "redis://b:6379"]
+        );
+        assert!(config.behind_proxy);
+        assert!(!config.behind_cloudflare_proxy);
+        assert_eq!(config.word_blocklist, vec!["foo", "bar"]);
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_invalid_config() {
+        let invalid_vars = [
+            ("PORT", "http"),
+            ("PORT", "65536"),
+            ("WORKERS", "-1"),
+            ("BEHIND_PROXY", "yes"),
+            ("WORD_BLOCKLIST", "foo"),
+            ("WORD_BLOCKLIST", "[1]"),
+            ("SERVER_SOFTWARE_RULES_RELOAD_INTERVAL", "1m"),
+            ("REDIS_MODE", "single"),
+        ];
+        for (name, value) in invalid_vars {
+            let mut config = Config::default();
+            assert!(
+                apply_env(&mut config, &[(name, value)]).is_err(),
+                "Expected error for {}={}",
+                name,
+                value
+            );
+        }
+
+        let invalid_vars: [&[(&str, &str)]; 8] = [
+            &[("REDIS_CLUSTER__URLS", "")],
+            &[("REDIS_CLUSTER__URLS", " , ")],
+            &[("REDIS_CLUSTER__URLS", "localhost:6379")],
+            &[("REDIS_MODE", "standalone")],
+            &[
+                ("REDIS_MODE", "sentinel"),
+                ("REDIS_SENTINEL__URLS", "redis://localhost:26379"),
+            ],
+            &[
+                ("REDIS_MODE", "sentinel"),
+                ("REDIS_SENTINEL__MASTER_NAME", "mymaster"),
+            ],
+            &[("WORKERS", "0")],
+            &[("SERVER_SOFTWARE_RULES_RELOAD_INTERVAL", "0")],
+        ];
+        for vars in invalid_vars {
+            let mut config = Config::default();
+            config.redis.cluster_urls = vec![String::from("redis://localhost:6379")];
+            apply_env(&mut config, vars).unwrap();
+            assert!(config.validate().is_err(), "Expected error for {:?}", vars);
+        }
+    }
+
+    #[test]
+    fn test_redis_modes() {
+        let mut config = Config::default();
+        apply_env(
+            &mut config,
+            &[
+                ("REDIS_MODE", "standalone"),
+                ("REDIS_URL", "redis://localhost:6379/1"),
+            ],
+        )
+        .unwrap();
+        assert_eq!(config.redis.mode, RedisMode::Standalone);
+        assert!(config.validate().is_ok());
+
+        let mut config = Config::default();
+        apply_env(
+            &mut config,
+            &[
+                ("REDIS_MODE", "sentinel"),
+                (
+                    "REDIS_SENTINEL__URLS",
+                    " redis://sentinel-1:26379 , redis://sentinel-2:26379 ",
+                ),
+                ("REDIS_SENTINEL__MASTER_NAME", "mymaster"),
+            ],
+        )
+        .unwrap();
+        assert_eq!(config.redis.mode, RedisMode::Sentinel);
+        assert_eq!(config.redis.sentinel_urls.len(), 2);
+        assert!(config.validate().is_ok());
+
+        let config: Config = toml::from_str(
+            r#"
+            [redis]
+            mode = "standalone"
+            url = "redis://localhost:6379"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.redis.mode, RedisMode::Standalone);
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_config_file() {
+        let mut config: Config = toml::from_str(
+            r#"
+            port = 80
+            behind_cloudflare_proxy = true
+
+            [redis]
+            cluster_urls = ["redis://localhost:6379"]
+
+            [server_software_rules]
+            path = "rules.toml"
+            "#,
+        )
+        .unwrap();
+        apply_env(&mut config, &[("PORT", "8081")]).unwrap();
+
+        assert_eq!(config.port, 8081);
+        assert!(config.behind_cloudflare_proxy);
+        assert_eq!(
+            config.server_software_rules.path,
+            Some(PathBuf::from("rules.toml"))
+        );
+        assert_eq!(config.server_software_rules.reload_interval, 60);
+        assert!(config.validate().is_ok());
+
+        assert!(toml::from_str::<Config>("prot = 80").is_err());
+    }
+}
@@ -1,37 +1,83 @@
 use std::collections::HashMap;
 use std::str::FromStr;
 
-use crate::chart_updater::update_chart;
+use crate::chart_updater::{execute_chart_pipeline, update_chart, ChartUpdate};
 use crate::charts;
+use crate::config::Config;
 use crate::date_util::date_to_tms2000;
-use crate::parser;
+use crate::metrics;
+use crate::parser::ParseContext;
 use crate::ratelimits::is_ratelimited;
 use crate::service;
 use crate::software;
+use crate::submission_report::{is_debug_mode, ChartStatus, SubmissionReport};
 use crate::submit_data_schema::SubmitDataChartSchema;
 use crate::submit_data_schema::SubmitDataSchema;
 use crate::submit_data_schema::SubmitDataServiceSchema;
 use crate::util::geo_ip;
 use crate::util::ip_parser;
-use crate::util::redis::RedisClusterPool;
-use actix_web::{error, web, HttpRequest, Responder};
-use once_cell::sync::Lazy;
+use crate::util::logging::hash_server_uuid;
+use crate::util::redis::RedisPool;
+use actix_web::{error, web, HttpRequest, HttpResponse};
+use tracing::Instrument;
 
+#[tracing::instrument(
+    name = "data_submission",
+    skip_all,
+    fields(
+        software_url,
+        service_id = data.service.id,
+        server_uuid = %hash_server_uuid(&data.server_uuid),
+        tms2000 = tracing::field::Empty,
+    )
+)]
 pub async fn handle_data_submission(
     request: &HttpRequest,
-    redis_pool: &web::Data<RedisClusterPool>,
+    redis_pool: &web::Data<RedisPool>,
+    config: &Config,
     software_url: &str,
     data: &SubmitDataSchema,
-    is_global_service: bool,
-) -> actix_web::Result<impl Responder> {
-    if has_blocked_words(&data) {
+) -> actix_web::Result<HttpResponse> {
+    if has_blocked_words(&data, &config.word_blocklist) {
         // Block silently
-        return Ok("");
+        tracing::debug!("Blocked submission with blocked words");
+        metrics::SUBMISSIONS.with_label_values(&["blocked"]).inc();
+        return Ok(HttpResponse::Ok().finish());
     }
 
+    let timer = metrics::SUBMISSION_DURATION.start_timer();
+    let result =
+        process_data_submission(request, redis_pool, config, software_url, data, false).await;
+    timer.observe_duration();
+
+    let label = match &result {
+        Ok(_) => "accepted",
+        Err(e) => match e.as_response_error().status_code().as_u16() {
+            429 => "ratelimited",
+            404 => "not_found",
+            400 => "bad_request",
+            _ => "error",
+        },
+    };
+    metrics::SUBMISSIONS.with_label_values(&[label]).inc();
+
+    result
+}
+
+async fn process_data_submission(
+    request: &HttpRequest,
+    redis_pool: &web::Data<RedisPool>,
+    config: &Config,
+    software_url: &str,
+    data: &SubmitDataSchema,
+    is_global_service: bool,
+) -> actix_web::Result<HttpResponse> {
     let mut con = match redis_pool.get().await {
         Ok(con) => con,
-        Err(e) => return Err(error::ErrorInternalServerError(e)),
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to get Redis connection");
+            return Err(error::ErrorInternalServerError(e));
+        }
     };
 
     let software = match software::find_by_url(&mut con, software_url).await {
@@ -40,9 +86,11 @@ pub async fn handle_data_submission(
         Ok(Some(s)) => s,
     };
 
-    let tms2000 = date_to_tms2000(chrono::Utc::now());
+    let received_at = chrono::Utc::now();
+    let tms2000 = date_to_tms2000(received_at);
+    tracing::Span::current().record("tms2000", tms2000);
 
-    let ip = ip_parser::get_ip(&request)?;
+    let ip = ip_parser::get_ip(&request, config)?;
 
     let ratelimit = is_ratelimited(
         &mut con,
@@ -56,14 +104,21 @@ pub async fn handle_data_submission(
     .await;
 
     match ratelimit {
-        Ok(true) => return Err(error::ErrorTooManyRequests("Too many requests")),
-        Err(e) => return Err(error::ErrorInternalServerError(e)),
+        Ok(true) => {
+            tracing::debug!("Rate limited");
+            return Err(error::ErrorTooManyRequests("Too many requests"));
+        }
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to check rate limit");
+            return Err(error::ErrorInternalServerError(e));
+        }
         Ok(false) => {}
     }
 
     // Global services are "fake" requests. We just recursively call this method
     // again, but with the data for the global service. Ratelimits ensure that
-    // this only happens once per server.
+    // this only happens once per server. The submission metrics are only
+    // recorded for the actual request.
     if !is_global_service && software.global_plugin.is_some() {
         let global_plugin = software.global_plugin.unwrap();
         let global_plugin = service::find_by_id(&mut con, global_plugin).await;
@@ -73,32 +128,34 @@ pub async fn handle_data_submission(
         };
 
         if let Some(global_plugin) = global_plugin {
-            let result = Box::pin(handle_data_submission(
-                request,
-                redis_pool,
-                software_url,
-                &SubmitDataSchema {
-                    server_uuid: data.server_uuid.clone(),
-                    metrics_version: data.metrics_version.clone(),
-                    extra: data.extra.clone(),
-                    service: SubmitDataServiceSchema {
-                        id: global_plugin.id,
-                        custom_charts: None,
-                        extra: HashMap::new(),
+            let result = Box::pin(
+                process_data_submission(
+                    request,
+                    redis_pool,
+                    config,
+                    software_url,
+                    &SubmitDataSchema {
+                        server_uuid: data.server_uuid.clone(),
+                        metrics_version: data.metrics_version.clone(),
+                        extra: data.extra.clone(),
+                        service: SubmitDataServiceSchema {
+                            id: global_plugin.id,
+                            custom_charts: None,
+                            extra: HashMap::new(),
+                        },
                     },
-                },
-                true,
-            ))
+                    true,
+                )
+                .instrument(tracing::info_span!(
+                    "global_service_submission",
+                    service_id = global_plugin.id
+                )),
+            )
             .await;
-            match result {
-                Ok(_) => {}
-                Err(e) => {
-                    if e.as_response_error().status_code() != 429 {
-                        // Too many requests can be ignored
-                    } else {
-                        // TODO Use proper logging framework
-                        println!("Error: {:?}", e);
-                    }
+            if let Err(e) = result {
+                // Too many requests can be ignored
+                if e.as_response_error().status_code() != 429 {
+                    tracing::warn!(error = %e, "Failed to process data for global service");
                 }
             }
         }
@@ -126,73 +183,103 @@ pub async fn handle_data_submission(
         None => (None, None),
     };
 
-    let default_charts: Vec<_> = software
-        .default_charts
-        .iter()
-        .filter_map(|template| {
-            parser::get_parser(template, country_name.clone()).and_then(|parser| {
-                Some(SubmitDataChartSchema {
-                    chart_id: template.id.clone(),
-                    data: parser.parse(&data)?,
-                    trusted: true,
-                })
-            })
-        })
-        .collect();
+    let parse_context = ParseContext {
+        schema: data,
+        ip: Some(&ip),
+        country_iso: country_iso.as_deref(),
+        country_name: country_name.as_deref(),
+        software: Some(&software),
+        service: Some(&service),
+        headers: Some(request.headers()),
+        received_at,
+    };
+    let mut report = SubmissionReport::default();
+    let mut default_charts = Vec::new();
+    for template in software.default_charts.iter() {
+        match template.parser().parse(&parse_context) {
+            Some(data) => default_charts.push(SubmitDataChartSchema {
+                chart_id: template.id.clone(),
+                data,
+                trusted: true,
+            }),
+            None => report.add(&template.id, ChartStatus::NoValue),
+        }
+    }
 
     let custom_charts = data.service.custom_charts.clone().unwrap_or(Vec::new());
     let chart_data = default_charts.iter().chain(custom_charts.iter());
 
-    let resolved_charts: std::collections::HashMap<u64, Option<charts::Chart>> =
-        charts::find_by_ids(&mut con, service.charts).await.unwrap();
+    let resolved_charts = charts::find_by_ids(&mut con, service.charts)
+        .await
+        .map_err(error::ErrorInternalServerError)?;
 
     let mut pipeline = redis::pipe();
 
     for chart_data in chart_data {
-        let service_chart: &charts::Chart = match resolved_charts
-            .values()
-            .filter_map(|c| c.as_ref())
-            .find(|c| c.id_custom == chart_data.chart_id)
-        {
-            Some(c) => c,
-            None => continue,
-        };
+        let service_chart: &charts::Chart =
+            match resolved_charts.values().flatten().find(|c| match c {
+                Ok(c) => c.id_custom == chart_data.chart_id,
+                Err(e) => e.id_custom == chart_data.chart_id,
+            }) {
+                Some(Ok(c)) => c,
+                Some(Err(e)) => {
+                    report.add_error(
+                        &chart_data.chart_id,
+                        ChartStatus::InvalidConfig,
+                        e.message.clone(),
+                    );
+                    continue;
+                }
+                None => {
+                    report.add(&chart_data.chart_id, ChartStatus::Unknown);
+                    continue;
+                }
+            };
 
         if !chart_data.trusted && service_chart.default {
             // The service is trying to trick us and sent a default chart as a custom chart
+            report.add(&chart_data.chart_id, ChartStatus::SpoofedDefaultChart);
             continue;
         }
 
-        let _ = update_chart(
+        match update_chart(
             service_chart,
             chart_data,
             tms2000,
             country_iso.as_deref(),
             &mut pipeline,
-            &mut con,
-        )
-        .await;
+        ) {
+            Ok(ChartUpdate::Updated) => report.add(&chart_data.chart_id, ChartStatus::Accepted),
+            Ok(ChartUpdate::Filtered) => report.add(&chart_data.chart_id, ChartStatus::Filtered),
+            Err(e) => {
+                report.add_error(&chart_data.chart_id, ChartStatus::ParseError, e.to_string())
+            }
+        }
     }
 
-    pipeline
-        .query_async(&mut con)
+    let timer = metrics::REDIS_DURATION
+        .with_label_values(&["chart_pipeline"])
+        .start_timer();
+    execute_chart_pipeline(&pipeline, &mut con)
         .await
-        .map_err(error::ErrorInternalServerError)?;
+        .map_err(|e| {
+            tracing::error!(error = %e, "Failed to write chart data");
+            error::ErrorInternalServerError(e)
+        })?;
+    timer.observe_duration();
 
-    Ok("")
-}
+    tracing::debug!(charts = report.charts.len(), "Processed submission");
 
-static WORD_BLOCKLIST: Lazy<Vec<String>> = Lazy::new(|| {
-    let word_blocklist = std::env::var("WORD_BLOCKLIST").unwrap_or(String::from("[]"));
-    match serde_json::from_str(&word_blocklist) {
-        Ok(blocklist) => blocklist,
-        Err(_) => Vec::new(),
+    if is_debug_mode(request) {
+        return Ok(HttpResponse::Ok().json(report));
     }
-});
 
-fn has_blocked_words(data: &SubmitDataSchema) -> bool {
+    Ok(HttpResponse::Ok().finish())
+}
+
+fn has_blocked_words(data: &SubmitDataSchema, word_blocklist: &[String]) -> bool {
     let mut blocked = false;
-    for word in WORD_BLOCKLIST.iter() {
+    for word in word_blocklist {
         // TODO: This is a very inefficient way to check for blocked words
         if serde_json::to_string(&data).unwrap().contains(word) {
             blocked = true;
@@ -0,0 +1,68 @@
+use std::time::Duration;
+
+use serde::Serialize;
+
+use crate::{
+    parser::server_software_rules,
+    util::{geo_ip, redis::RedisPool},
+};
+
+/// How long the Redis check may take before the instance is considered not ready.
+const REDIS_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// The result of the readiness checks, returned by `GET /ready`.
+#[derive(Debug, Serialize)]
+pub struct Readiness {
+    pub ready: bool,
+    pub checks: ReadinessChecks,
+}
+
+/// The result of every check, either `"ok"` or the reason why it failed.
+#[derive(Debug, Serialize)]
+pub struct ReadinessChecks {
+    pub redis: String,
+    pub geoip: String,
+    pub server_software_rules: String,
+}
+
+pub async fn check_readiness(redis_pool: &RedisPool) -> Readiness {
+    let checks = ReadinessChecks {
+        redis: to_status(check_redis(redis_pool).await),
+        geoip: to_status(check_geoip()),
+        server_software_rules: to_status(server_software_rules::check_rules_loaded()),
+    };
+    Readiness {
+        ready: checks.redis == "ok" && checks.geoip == "ok" && checks.server_software_rules == "ok",
+        checks,
+    }
+}
+
+fn to_status(result: Result<(), String>) -> String {
+    match result {
+        Ok(()) => String::from("ok"),
+        Err(e) => e,
+    }
+}
+
+/// Checks that a connection can be taken from the pool and answers a `PING`.
+async fn check_redis(redis_pool: &RedisPool) -> Result<(), String> {
+    let ping = async {
+        let mut con = redis_pool.get().await.map_err(|e| e.to_string())?;
+        redis::cmd("PING")
+            .query_async::<String>(&mut con)
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    };
+    actix_web::rt::time::timeout(REDIS_TIMEOUT, ping)
+        .await
+        .map_err(|_| String::from("timed out"))?
+}
+
+fn check_geoip() -> Result<(), String> {
+    if geo_ip::is_loaded() {
+        Ok(())
+    } else {
+        Err(String::from("GeoIP database not loaded"))
+    }
+}
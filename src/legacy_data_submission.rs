@@ -8,10 +8,11 @@ use serde_with::skip_serializing_none;
 use validator::Validate;
 
 use crate::{
+    config::Config,
     data_submission::handle_data_submission,
     service,
     submit_data_schema::{SubmitDataChartSchema, SubmitDataSchema, SubmitDataServiceSchema},
-    util::redis::RedisClusterPool,
+    util::{logging::hash_server_uuid, redis::RedisPool},
 };
 
 #[skip_serializing_none]
@@ -47,9 +48,19 @@ pub struct LegacySubmitDataServiceSchema {
     pub extra: HashMap<String, Value>,
 }
 
+#[tracing::instrument(
+    name = "legacy_data_submission",
+    skip_all,
+    fields(
+        software_url,
+        server_uuid = %hash_server_uuid(&data.server_uuid),
+        plugins = data.plugins.len(),
+    )
+)]
 pub async fn handle_legacy_data_submission(
     request: &HttpRequest,
-    redis_pool: &web::Data<RedisClusterPool>,
+    redis_pool: &web::Data<RedisPool>,
+    config: &Config,
     software_url: &str,
     data: LegacySubmitDataSchema,
 ) -> actix_web::Result<impl Responder> {
@@ -78,9 +89,10 @@ pub async fn handle_legacy_data_submission(
             }
         };
 
-        let _ = handle_data_submission(
+        let result = handle_data_submission(
             &request,
             &redis_pool,
+            config,
             software_url,
             &SubmitDataSchema {
                 server_uuid: data.server_uuid.clone(),
@@ -92,9 +104,11 @@ pub async fn handle_legacy_data_submission(
                     extra: plugin.extra,
                 },
             },
-            false,
         )
         .await;
+        if let Err(e) = result {
+            tracing::debug!(plugin_id, error = %e, "Failed to process legacy plugin data");
+        }
     }
 
     Ok("")
@@ -1,33 +1,39 @@
 pub mod chart_updater;
 pub mod charts;
+pub mod config;
 pub mod data_submission;
 pub mod date_util;
+pub mod health;
 pub mod legacy_data_submission;
+pub mod metrics;
 pub mod parser;
 pub mod ratelimits;
 pub mod service;
 pub mod software;
+pub mod submission_report;
 pub mod submit_data_schema;
 pub mod util;
 
-use actix_web::{post, web, HttpRequest, Responder};
+use actix_web::{get, post, web, HttpRequest, HttpResponse, Responder};
+use config::Config;
 use legacy_data_submission::LegacySubmitDataSchema;
 use submit_data_schema::SubmitDataSchema;
-use util::redis::RedisClusterPool;
+use util::redis::RedisPool;
 
 #[post("/{software_url}")]
 async fn submit_data(
     request: HttpRequest,
-    redis_pool: web::Data<RedisClusterPool>,
+    redis_pool: web::Data<RedisPool>,
+    config: web::Data<Config>,
     software_url: web::Path<String>,
     data: web::Json<SubmitDataSchema>,
 ) -> actix_web::Result<impl Responder> {
     data_submission::handle_data_submission(
         &request,
         &redis_pool,
+        &config,
         software_url.as_str(),
         &data.0,
-        false,
     )
     .await
 }
@@ -35,15 +41,41 @@ async fn submit_data(
 #[post("/legacy/{software_url}")]
 async fn legacy_submit_data(
     request: HttpRequest,
-    redis_pool: web::Data<RedisClusterPool>,
+    redis_pool: web::Data<RedisPool>,
+    config: web::Data<Config>,
     software_url: web::Path<String>,
     data: web::Json<LegacySubmitDataSchema>,
 ) -> actix_web::Result<impl Responder> {
     legacy_data_submission::handle_legacy_data_submission(
         &request,
         &redis_pool,
+        &config,
         software_url.as_str(),
         data.0,
     )
     .await
 }
+
+#[get("/metrics")]
+async fn metrics_endpoint(redis_pool: web::Data<RedisPool>) -> actix_web::Result<impl Responder> {
+    metrics::update_pool_metrics(&redis_pool);
+    let body = metrics::encode().map_err(actix_web::error::ErrorInternalServerError)?;
+    Ok(HttpResponse::Ok()
+        .content_type("text/plain; version=0.0.4")
+        .body(body))
+}
+
+#[get("/health")]
+async fn health_endpoint() -> impl Responder {
+    HttpResponse::Ok().body("OK")
+}
+
+#[get("/ready")]
+async fn ready_endpoint(redis_pool: web::Data<RedisPool>) -> impl Responder {
+    let readiness = health::check_readiness(&redis_pool).await;
+    if readiness.ready {
+        HttpResponse::Ok().json(readiness)
+    } else {
+        HttpResponse::ServiceUnavailable().json(readiness)
+    }
+}
@@ -1,25 +1,53 @@
 use actix_web::{web, App, HttpServer};
-use data_processor::{legacy_submit_data, submit_data, util::redis::get_redis_cluster_pool};
+use data_processor::{
+    config::Config,
+    health_endpoint, legacy_submit_data, metrics_endpoint,
+    parser::server_software_rules::spawn_rules_reloader,
+    ready_endpoint, submit_data,
+    util::{geo_ip, logging::init_logging, redis::get_redis_pool},
+};
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
-    let host = std::env::var("HOST").unwrap_or_else(|_| "0.0.0.0".to_string());
-    let port: u16 = std::env::var("PORT")
-        .unwrap_or_else(|_| "8080".to_string())
-        .parse()
-        .unwrap();
+    init_logging();
 
-    let pool = get_redis_cluster_pool().await;
+    let config = match Config::load() {
+        Ok(config) => config,
+        Err(e) => {
+            tracing::error!(error = %e, "Invalid configuration");
+            std::process::exit(1);
+        }
+    };
+
+    geo_ip::load_database(&config.geoip_database_path);
+
+    let pool = match get_redis_pool(&config.redis).await {
+        Ok(pool) => pool,
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to create Redis pool");
+            std::process::exit(1);
+        }
+    };
+    spawn_rules_reloader(pool.clone(), &config.server_software_rules);
+
+    let host = config.host.clone();
+    let port = config.port;
+    let workers = config.workers;
+    let config = web::Data::new(config);
 
     let mut http_server = HttpServer::new(move || {
         App::new()
             .app_data(web::Data::new(pool.clone()))
+            .app_data(config.clone())
             .service(submit_data)
             .service(legacy_submit_data)
+            .service(metrics_endpoint)
+            .service(health_endpoint)
+            .service(ready_endpoint)
     });
 
-    if let Ok(workers) = std::env::var("WORKERS") {
-        http_server = http_server.workers(workers.parse().unwrap());
+    if let Some(workers) = workers {
+        http_server = http_server.workers(workers);
     }
 
     http_server.bind((host, port))?.run().await
@@ -0,0 +1,127 @@
+use once_cell::sync::Lazy;
+use prometheus::{
+    register_histogram, register_histogram_vec, register_int_counter_vec, register_int_gauge_vec,
+    Encoder, Histogram, HistogramVec, IntCounterVec, IntGaugeVec, TextEncoder,
+};
+
+use crate::util::redis::RedisPool;
+
+/// Submissions by their result, e.g. `accepted` or `ratelimited`.
+pub static SUBMISSIONS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "data_processor_submissions_total",
+        "Number of data submissions by result",
+        &["result"]
+    )
+    .unwrap()
+});
+
+/// The duration of [`handle_data_submission`](crate::data_submission::handle_data_submission).
+pub static SUBMISSION_DURATION: Lazy<Histogram> = Lazy::new(|| {
+    register_histogram!(
+        "data_processor_submission_duration_seconds",
+        "Duration of processing a data submission"
+    )
+    .unwrap()
+});
+
+/// Rate limit hits by what was limited (`server` or `ip`).
+pub static RATELIMIT_HITS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "data_processor_ratelimit_hits_total",
+        "Number of requests that exceeded a rate limit",
+        &["limit"]
+    )
+    .unwrap()
+});
+
+/// Chart updates by chart type and result (`updated`, `filtered` or `parse_error`).
+pub static CHART_UPDATES: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "data_processor_chart_updates_total",
+        "Number of chart updates by chart type and result",
+        &["chart_type", "result"]
+    )
+    .unwrap()
+});
+
+/// GeoIP lookups by result (`found`, `not_found` or `error`).
+pub static GEOIP_LOOKUPS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "data_processor_geoip_lookups_total",
+        "Number of GeoIP lookups by result",
+        &["result"]
+    )
+    .unwrap()
+});
+
+/// Servers whose software is reported as "Unknown", by their `bukkitName`.
+pub static UNKNOWN_SERVER_SOFTWARE: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "data_processor_unknown_server_software_total",
+        "Number of parsed submissions with an unknown server software by bukkitName",
+        &["bukkit_name"]
+    )
+    .unwrap()
+});
+
+/// The latency of Redis operations by operation.
+pub static REDIS_DURATION: Lazy<HistogramVec> = Lazy::new(|| {
+    register_histogram_vec!(
+        "data_processor_redis_duration_seconds",
+        "Duration of Redis operations",
+        &["operation"]
+    )
+    .unwrap()
+});
+
+/// The connections of the Redis pool by state (`in_use`, `idle` or `max`).
+pub static REDIS_POOL_CONNECTIONS: Lazy<IntGaugeVec> = Lazy::new(|| {
+    register_int_gauge_vec!(
+        "data_processor_redis_pool_connections",
+        "Number of connections in the Redis pool by state",
+        &["state"]
+    )
+    .unwrap()
+});
+
+/// Updates the metrics that are only read on demand.
+pub fn update_pool_metrics(redis_pool: &RedisPool) {
+    let status = redis_pool.status();
+    let idle = status.available as i64;
+    REDIS_POOL_CONNECTIONS
+        .with_label_values(&["in_use"])
+        .set(status.size as i64 - idle);
+    REDIS_POOL_CONNECTIONS
+        .with_label_values(&["idle"])
+        .set(idle);
+    REDIS_POOL_CONNECTIONS
+        .with_label_values(&["max"])
+        .set(status.max_size as i64);
+}
+
+/// Encodes all metrics in the Prometheus text format.
+pub fn encode() -> Result<String, prometheus::Error> {
+    let mut buffer = Vec::new();
+    TextEncoder::new().encode(&prometheus::gather(), &mut buffer)?;
+    Ok(String::from_utf8(buffer).unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode() {
+        SUBMISSIONS.with_label_values(&["accepted"]).inc();
+        CHART_UPDATES
+            .with_label_values(&["simple_pie", "updated"])
+            .inc();
+
+        let metrics = encode().unwrap();
+        assert!(metrics.contains("data_processor_submissions_total{result=\"accepted\"}"));
+        assert!(metrics.contains(
+            "data_processor_chart_updates_total{chart_type=\"simple_pie\",result=\"updated\"}"
+        ));
+    }
+}
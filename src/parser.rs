@@ -1,62 +1,89 @@
+use std::{fmt, sync::Arc};
+
+use actix_web::http::header::HeaderMap;
+use chrono::{DateTime, Utc};
 use serde_json::Value;
 
-use crate::{charts::chart::DefaultChartTemplate, submit_data_schema::SubmitDataSchema};
+use crate::{service::Service, software::Software, submit_data_schema::SubmitDataSchema};
+use request_parser_config::RequestParserConfig;
 
+pub mod architecture;
 pub mod bukkit_minecraft_version;
 pub mod bukkit_server_software;
 pub mod bungeecord_version;
 pub mod java_version;
+pub mod mod_loader;
 pub mod name_in_request;
+pub mod numeric_bucket;
 pub mod os;
+pub mod platform_version;
 pub mod predefined_value;
+pub mod registry;
+pub mod request_parser_config;
+pub mod server_software_rules;
+pub mod sponge_version;
+pub mod transform;
+pub mod velocity_version;
 
-pub trait Parser {
-    fn parse(&self, schema: &SubmitDataSchema) -> Option<Value>;
+pub trait Parser: Send + Sync {
+    fn parse(&self, context: &ParseContext) -> Option<Value>;
 }
 
-pub fn get_parser(
-    template: &DefaultChartTemplate,
-    country_name: Option<String>,
-) -> Option<Box<dyn Parser>> {
-    if let Some(predefined_value) = template.request_parser.get("predefinedValue") {
-        return Some(Box::new(predefined_value::PredefinedValueParser {
-            value: predefined_value.clone(),
-            country_name,
-        }));
+impl fmt::Debug for dyn Parser {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Parser")
     }
+}
 
-    let hardcoded_parser = template
-        .request_parser
-        .get("useHardcodedParser")
-        .and_then(|v| v.as_str());
-
-    match hardcoded_parser {
-        Some("os") => return Some(Box::new(os::OsParser)),
-        Some("javaVersion") => return Some(Box::new(java_version::JavaVersionParser)),
-        Some("bukkitMinecraftVersion") => {
-            return Some(Box::new(
-                bukkit_minecraft_version::BukkitMinecraftVersionParser,
-            ))
-        }
-        Some("bukkitServerSoftware") => {
-            return Some(Box::new(bukkit_server_software::BukkitServerSoftwareParser))
-        }
-        Some("bungeecordVersion") => {
-            return Some(Box::new(bungeecord_version::BungeecordVersionParser))
-        }
-        _ => (),
-    }
+/// Everything that is known about a submission when the default charts are parsed.
+pub struct ParseContext<'a> {
+    pub schema: &'a SubmitDataSchema,
+    /// The IP address of the server, as resolved by [`get_ip`](crate::util::ip_parser::get_ip).
+    pub ip: Option<&'a str>,
+    pub country_iso: Option<&'a str>,
+    pub country_name: Option<&'a str>,
+    pub software: Option<&'a Software>,
+    pub service: Option<&'a Service>,
+    pub headers: Option<&'a HeaderMap>,
+    /// When the submission was received.
+    pub received_at: DateTime<Utc>,
+}
 
-    if let Some(name_in_request) = template.request_parser.get("nameInRequest") {
-        return Some(Box::new(name_in_request::NameInRequestParser {
-            name_in_request: name_in_request.as_str()?.to_string(),
-            position: template
-                .request_parser
-                .get("position")?
-                .as_str()?
-                .to_string(),
-        }));
+impl<'a> ParseContext<'a> {
+    /// Creates a context that only contains the submitted data.
+    pub fn new(schema: &'a SubmitDataSchema) -> Self {
+        ParseContext {
+            schema,
+            ip: None,
+            country_iso: None,
+            country_name: None,
+            software: None,
+            service: None,
+            headers: None,
+            received_at: Utc::now(),
+        }
     }
+}
 
-    return None;
+/// Creates the parser for a `requestParser` config.
+pub fn create_parser(config: &RequestParserConfig) -> Result<Arc<dyn Parser>, String> {
+    Ok(match config {
+        RequestParserConfig::PredefinedValue { value } => {
+            Arc::new(predefined_value::PredefinedValueParser {
+                value: value.clone(),
+            })
+        }
+        RequestParserConfig::HardcodedParser { name, options, .. } => {
+            Arc::from(registry::create_hardcoded_parser(name, options)?)
+        }
+        RequestParserConfig::NameInRequest {
+            name,
+            position,
+            transforms,
+        } => Arc::new(name_in_request::NameInRequestParser {
+            name_in_request: name.clone(),
+            position: *position,
+            transforms: transforms.clone(),
+        }),
+    })
 }
@@ -0,0 +1,102 @@
+use serde_json::{json, Value};
+
+use crate::charts::simple_pie::SimplePie;
+
+use super::{ParseContext, Parser};
+
+pub struct ArchitectureParser;
+
+impl Parser for ArchitectureParser {
+    fn parse(&self, context: &ParseContext) -> Option<Value> {
+        let os_arch = context
+            .schema
+            .extra
+            .get("osArch")
+            .and_then(|v| v.as_str())?;
+        Some(json!(SimplePie {
+            value: normalize_architecture(os_arch)
+        }))
+    }
+}
+
+/// Normalizes the different names of the same architecture, e.g. "amd64" (Java's `os.arch`)
+/// and "x86_64" (`uname -m`).
+pub fn normalize_architecture(os_arch: &str) -> String {
+    let os_arch = os_arch.trim().to_ascii_lowercase();
+    match os_arch.as_str() {
+        "amd64" | "x86_64" | "x86-64" | "x64" | "em64t" => String::from("x86_64"),
+        "x86" | "i386" | "i486" | "i586" | "i686" => String::from("x86"),
+        "aarch64" | "arm64" | "armv8" => String::from("aarch64"),
+        "arm" | "armv7" | "armv7l" | "armv6l" | "armhf" | "aarch32" => String::from("arm"),
+        "ppc64le" | "powerpc64le" => String::from("ppc64le"),
+        "riscv64" | "riscv" => String::from("riscv64"),
+        _ => os_arch,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_normalize_architecture() {
+        struct TestCase {
+            name: String,
+            os_arch: String,
+            expected: String,
+        }
+
+        let test_cases = vec![
+            TestCase {
+                name: String::from("Java amd64"),
+                os_arch: String::from("amd64"),
+                expected: String::from("x86_64"),
+            },
+            TestCase {
+                name: String::from("uname x86_64"),
+                os_arch: String::from("x86_64"),
+                expected: String::from("x86_64"),
+            },
+            TestCase {
+                name: String::from("32 bit x86"),
+                os_arch: String::from("i386"),
+                expected: String::from("x86"),
+            },
+            TestCase {
+                name: String::from("Java aarch64"),
+                os_arch: String::from("aarch64"),
+                expected: String::from("aarch64"),
+            },
+            TestCase {
+                name: String::from("Apple Silicon arm64"),
+                os_arch: String::from("arm64"),
+                expected: String::from("aarch64"),
+            },
+            TestCase {
+                name: String::from("Raspberry Pi armv7l"),
+                os_arch: String::from("armv7l"),
+                expected: String::from("arm"),
+            },
+            TestCase {
+                name: String::from("Upper case"),
+                os_arch: String::from("AMD64"),
+                expected: String::from("x86_64"),
+            },
+            TestCase {
+                name: String::from("Unknown"),
+                os_arch: String::from("s390x"),
+                expected: String::from("s390x"),
+            },
+        ];
+
+        for test_case in test_cases {
+            let result = normalize_architecture(&test_case.os_arch);
+            assert_eq!(
+                result, test_case.expected,
+                "Wrong result for {}",
+                test_case.name
+            );
+        }
+    }
+}
@@ -1,16 +1,19 @@
-use crate::{charts::simple_pie::SimplePie, submit_data_schema::SubmitDataSchema};
+use std::collections::HashMap;
+
+use crate::charts::{drilldown_pie::DrilldownPie, simple_pie::SimplePie};
 use once_cell::sync::Lazy;
 use regex::Regex;
 use serde_json::{json, Value};
 
-use super::Parser;
+use super::{ParseContext, Parser};
 
 pub struct BukkitMinecraftVersionParser;
 
 impl Parser for BukkitMinecraftVersionParser {
-    fn parse(&self, schema: &SubmitDataSchema) -> Option<Value> {
+    fn parse(&self, context: &ParseContext) -> Option<Value> {
         let version = parse_bukkit_minecraft_version(
-            schema
+            context
+                .schema
                 .extra
                 .get("bukkitVersion")
                 .and_then(|v| v.as_str())
@@ -20,7 +23,32 @@ impl Parser for BukkitMinecraftVersionParser {
     }
 }
 
+/// Groups the patch versions under their minor release, e.g. `1.20` → `1.20.1`.
+pub struct BukkitMinecraftVersionDrilldownParser;
+
+impl Parser for BukkitMinecraftVersionDrilldownParser {
+    fn parse(&self, context: &ParseContext) -> Option<Value> {
+        let version = parse_full_bukkit_minecraft_version(
+            context
+                .schema
+                .extra
+                .get("bukkitVersion")
+                .and_then(|v| v.as_str()),
+        )?;
+        Some(json!(DrilldownPie {
+            values: HashMap::from([(
+                get_minecraft_minor_version(&version),
+                HashMap::from([(version, 1)])
+            )])
+        }))
+    }
+}
+
 static RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"MC: ([\d\\.]+)").unwrap());
+// Unlike RE, this also includes the pre-release and release candidate suffixes
+static RE_FULL: Lazy<Regex> = Lazy::new(|| Regex::new(r"MC: ([^)]+)\)").unwrap());
+static RE_MINOR: Lazy<Regex> = Lazy::new(|| Regex::new(r"^(\d+\.\d+)(?:[.\- ]|$)").unwrap());
+static RE_SNAPSHOT: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\d{2}w\d{2}[a-z]$").unwrap());
 
 fn parse_bukkit_minecraft_version(bukkit_version: Option<&str>) -> Option<String> {
     bukkit_version.map(|bukkit_version| {
@@ -38,6 +66,31 @@ fn parse_bukkit_minecraft_version(bukkit_version: Option<&str>) -> Option<String
     })
 }
 
+fn parse_full_bukkit_minecraft_version(bukkit_version: Option<&str>) -> Option<String> {
+    let bukkit_version = bukkit_version?;
+    if !bukkit_version.contains("MC:") {
+        // Legacy bStats Metrics class, see parse_bukkit_minecraft_version
+        return Some(bukkit_version.to_string());
+    }
+    RE_FULL
+        .captures(bukkit_version)
+        .and_then(|captures| captures.get(1))
+        .map(|version| version.as_str().trim().to_string())
+        .or_else(|| parse_bukkit_minecraft_version(Some(bukkit_version)))
+}
+
+/// Returns the minor release a version belongs to, e.g. "1.20" for "1.20.1" or "1.21-pre1".
+/// Snapshots like "24w14a" are grouped as "Snapshots".
+fn get_minecraft_minor_version(version: &str) -> String {
+    if let Some(captures) = RE_MINOR.captures(version) {
+        return captures[1].to_string();
+    }
+    if RE_SNAPSHOT.is_match(version) {
+        return String::from("Snapshots");
+    }
+    version.to_string()
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -90,4 +143,73 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_parse_bukkit_minecraft_version_drilldown() {
+        struct TestCase {
+            name: String,
+            bukkit_version: String,
+            expected: (String, String),
+        }
+
+        let test_cases = vec![
+            TestCase {
+                name: String::from("Paper 1.20.1"),
+                bukkit_version: String::from("git-Paper-196 (MC: 1.20.1)"),
+                expected: (String::from("1.20"), String::from("1.20.1")),
+            },
+            TestCase {
+                name: String::from("Paper 1.21"),
+                bukkit_version: String::from("1.21-38-1f5db50 (MC: 1.21)"),
+                expected: (String::from("1.21"), String::from("1.21")),
+            },
+            TestCase {
+                name: String::from("Pre-release"),
+                bukkit_version: String::from("1.21.4-DEV-5cf2a1a (MC: 1.21.4-pre1)"),
+                expected: (String::from("1.21"), String::from("1.21.4-pre1")),
+            },
+            TestCase {
+                name: String::from("Release candidate"),
+                bukkit_version: String::from("1.21-DEV-1a2b3c4 (MC: 1.21-rc1)"),
+                expected: (String::from("1.21"), String::from("1.21-rc1")),
+            },
+            TestCase {
+                name: String::from("Pre-release with spaces"),
+                bukkit_version: String::from("git-Custom-1 (MC: 1.20 Pre-Release 2)"),
+                expected: (String::from("1.20"), String::from("1.20 Pre-Release 2")),
+            },
+            TestCase {
+                name: String::from("Snapshot"),
+                bukkit_version: String::from("git-Custom-1 (MC: 24w14a)"),
+                expected: (String::from("Snapshots"), String::from("24w14a")),
+            },
+            TestCase {
+                name: String::from("Year based version"),
+                bukkit_version: String::from("26.1.1-12-abcdef0 (MC: 26.1.1)"),
+                expected: (String::from("26.1"), String::from("26.1.1")),
+            },
+            TestCase {
+                name: String::from("Legacy Metrics Class"),
+                bukkit_version: String::from("1.8.8"),
+                expected: (String::from("1.8"), String::from("1.8.8")),
+            },
+            TestCase {
+                name: String::from("Garbage"),
+                bukkit_version: String::from("garbage"),
+                expected: (String::from("garbage"), String::from("garbage")),
+            },
+        ];
+
+        for test_case in test_cases {
+            let version =
+                parse_full_bukkit_minecraft_version(Some(&test_case.bukkit_version)).unwrap();
+            let result = (get_minecraft_minor_version(&version), version);
+
+            assert_eq!(
+                result, test_case.expected,
+                "Wrong result for {}",
+                test_case.name
+            );
+        }
+    }
 }
@@ -1,21 +1,26 @@
+use std::{collections::HashSet, sync::Mutex};
+
+use once_cell::sync::Lazy;
 use phf::phf_ordered_map;
 use serde_json::{json, Value};
 
-use crate::{charts::simple_pie::SimplePie, submit_data_schema::SubmitDataSchema};
+use crate::{charts::simple_pie::SimplePie, metrics};
 
-use super::Parser;
+use super::{server_software_rules, ParseContext, Parser};
 
 pub struct BukkitServerSoftwareParser;
 
 impl Parser for BukkitServerSoftwareParser {
-    fn parse(&self, schema: &SubmitDataSchema) -> Option<Value> {
+    fn parse(&self, context: &ParseContext) -> Option<Value> {
         let software_name = parse_bukkit_server_software(
-            schema
+            context
+                .schema
                 .extra
                 .get("bukkitVersion")
                 .and_then(|v| v.as_str())
                 .as_deref(),
-            schema
+            context
+                .schema
                 .extra
                 .get("bukkitName")
                 .and_then(|v| v.as_str())
@@ -31,6 +36,8 @@ static SERVER_SOFTWARE_BRANDS: phf::OrderedMap<&'static str, &'static str> = phf
     "bukkit" => "Bukkit", // https://github.com/Bukkit/Bukkit, EOL
     // The order is important here -> TacoSpigot before Spigot or it will be detected as Spigot
     "taco" => "TacoSpigot", // https://github.com/TacoSpigot/TacoSpigot, EOL
+    "nachospigot" => "NachoSpigot", // https://github.com/CobbleSword/NachoSpigot
+    "pandaspigot" => "PandaSpigot", // https://github.com/hpfxd/PandaSpigot
     "paper" => "Paper", // https://github.com/PaperMC/Paper
     "folia" => "Folia", // https://github.com/PaperMC/Folia
     "spigot" => "Spigot", // https://hub.spigotmc.org/stash/projects/SPIGOT/repos/spigot/browse
@@ -50,8 +57,52 @@ static SERVER_SOFTWARE_BRANDS: phf::OrderedMap<&'static str, &'static str> = phf
     "pufferfish" => "Pufferfish", // https://github.com/pufferfish-gg/Pufferfish
     "leaves" => "Leaves", // https://github.com/LeavesMC/Leaves
     "leaf" => "Leaf", // https://github.com/Winds-Studio/Leaf
+    "akarin" => "Akarin", // https://github.com/Akarin-project/Akarin, EOL
+    "plazma" => "Plazma", // https://github.com/PlazmaMC/PlazmaBukkit
+    "divinemc" => "DivineMC", // https://github.com/BX-Team/DivineMC
+    "kaiiju" => "Kaiiju", // https://github.com/KaiijuMC/Kaiiju, EOL
+    "mirai" => "Mirai", // https://github.com/etil2jz/Mirai, EOL
+    "luminol" => "Luminol", // https://github.com/LuminolMC/Luminol
+    "canvas" => "Canvas", // https://github.com/CraftCanvasMC/Canvas
+    "ketting" => "Ketting", // https://github.com/kettingpowered/Ketting-1-20-x
+    "youer" => "Youer", // https://github.com/MohistMC/Youer
 };
 
+/// The maximum amount of different unknown server software names that are recorded.
+///
+/// Every name is a label of [`UNKNOWN_SERVER_SOFTWARE`](metrics::UNKNOWN_SERVER_SOFTWARE),
+/// so this limits the cardinality of the metric.
+const MAX_UNKNOWN_SERVER_SOFTWARE: usize = 1000;
+
+/// The maximum length of a recorded name. Longer names are truncated.
+const MAX_UNKNOWN_SERVER_SOFTWARE_NAME_LENGTH: usize = 64;
+
+/// The `bukkitName`s of the unknown server software that was recorded by this process.
+static UNKNOWN_SERVER_SOFTWARE_NAMES: Lazy<Mutex<HashSet<String>>> =
+    Lazy::new(|| Mutex::new(HashSet::new()));
+
+/// Counts a server software that is reported as "Unknown" in the metrics, so that forks
+/// which should be added can be found.
+fn record_unknown_server_software(bukkit_version: &str, bukkit_name: Option<&str>) {
+    let bukkit_name: String = bukkit_name
+        .unwrap_or("<not set>")
+        .chars()
+        .take(MAX_UNKNOWN_SERVER_SOFTWARE_NAME_LENGTH)
+        .collect();
+    let mut names = UNKNOWN_SERVER_SOFTWARE_NAMES.lock().unwrap();
+    if !names.contains(&bukkit_name) {
+        if names.len() >= MAX_UNKNOWN_SERVER_SOFTWARE {
+            return;
+        }
+        // Only logged once per software, so this does not flood the logs
+        tracing::info!(bukkit_version, bukkit_name, "Unknown server software");
+        names.insert(bukkit_name.clone());
+    }
+    metrics::UNKNOWN_SERVER_SOFTWARE
+        .with_label_values(&[&bukkit_name])
+        .inc();
+}
+
 fn parse_bukkit_server_software(
     bukkit_version: Option<&str>,
     bukkit_name: Option<&str>,
@@ -63,6 +114,13 @@ fn parse_bukkit_server_software(
         return None;
     }
 
+    // Rules that are loaded at runtime take precedence over the compiled-in table
+    if let Some(software_name) =
+        server_software_rules::find_software_name(bukkit_version, bukkit_name)
+    {
+        return Some(software_name);
+    }
+
     // First try to find the software name based on the bukkit version
     let bukkit_version_lower = bukkit_version.to_ascii_lowercase();
     let software_name = SERVER_SOFTWARE_BRANDS
@@ -78,12 +136,7 @@ fn parse_bukkit_server_software(
             })
         })
         .unwrap_or_else(|| {
-            // TODO Use proper logging framework that does not block stdout
-            // println!(
-            //     "Unknown server software: bukkitVersion='{}', bukkitName='{}'",
-            //     bukkit_version,
-            //     bukkit_name.unwrap_or("<not set>")
-            // );
+            record_unknown_server_software(bukkit_version, bukkit_name);
             "Unknown"
         });
 
@@ -195,6 +248,24 @@ mod tests {
                 bukkit_name: String::from("Pufferfish"),
                 expected: String::from("Pufferfish"),
             },
+            TestCase {
+                name: String::from("PandaSpigot 1.8.8"),
+                bukkit_version: String::from("git-PandaSpigot-109 (MC: 1.8.8)"),
+                bukkit_name: String::from("PandaSpigot"),
+                expected: String::from("PandaSpigot"),
+            },
+            TestCase {
+                name: String::from("Luminol 1.21"),
+                bukkit_version: String::from("1.21-DEV-0b1a6b4 (MC: 1.21)"),
+                bukkit_name: String::from("Luminol"),
+                expected: String::from("Luminol"),
+            },
+            TestCase {
+                name: String::from("Unknown fork"),
+                bukkit_version: String::from("1.21-7-abcdef0 (MC: 1.21)"),
+                bukkit_name: String::from("SomeUnknownFork"),
+                expected: String::from("Unknown"),
+            },
         ];
 
         for test_case in test_cases {
@@ -211,4 +282,22 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_record_unknown_server_software() {
+        let bukkit_version = "1.21-1-0000000 (MC: 1.21)";
+        for _ in 0..3 {
+            let result =
+                parse_bukkit_server_software(Some(bukkit_version), Some("RecordedUnknownFork"));
+            assert_eq!(result.unwrap(), "Unknown");
+        }
+
+        let count = |name: &str| {
+            metrics::UNKNOWN_SERVER_SOFTWARE
+                .with_label_values(&[name])
+                .get()
+        };
+        assert_eq!(count("RecordedUnknownFork"), 3);
+        assert_eq!(count("Paper"), 0);
+    }
 }
@@ -1,14 +1,15 @@
 use serde_json::{json, Value};
 
-use crate::{charts::simple_pie::SimplePie, submit_data_schema::SubmitDataSchema};
+use crate::charts::simple_pie::SimplePie;
 
-use super::Parser;
+use super::{ParseContext, Parser};
 
 pub struct BungeecordVersionParser;
 
 impl Parser for BungeecordVersionParser {
-    fn parse(&self, schema: &SubmitDataSchema) -> Option<Value> {
-        let version = schema
+    fn parse(&self, context: &ParseContext) -> Option<Value> {
+        let version = context
+            .schema
             .extra
             .get("bungeecordVersion")
             .and_then(|v| v.as_str())
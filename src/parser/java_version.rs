@@ -2,15 +2,19 @@ use std::collections::HashMap;
 
 use serde_json::{json, Value};
 
-use crate::{charts::drilldown_pie::DrilldownPie, submit_data_schema::SubmitDataSchema};
+use crate::charts::drilldown_pie::DrilldownPie;
 
-use super::Parser;
+use super::{ParseContext, Parser};
 
 pub struct JavaVersionParser;
 
 impl Parser for JavaVersionParser {
-    fn parse(&self, schema: &SubmitDataSchema) -> Option<Value> {
-        let java_version = schema.extra.get("javaVersion").and_then(|v| v.as_str())?;
+    fn parse(&self, context: &ParseContext) -> Option<Value> {
+        let java_version = context
+            .schema
+            .extra
+            .get("javaVersion")
+            .and_then(|v| v.as_str())?;
         let major_version = get_java_major_version(java_version);
         Some(json!(DrilldownPie {
             values: HashMap::from([(
@@ -24,17 +28,82 @@ impl Parser for JavaVersionParser {
 fn get_java_major_version(java_version: &str) -> &str {
     // Java versions post Java 9 have the format "$MAJOR.$MINOR.$SECURITY.$PATCH"
     // Java versions pre Java 9 have the format "1.$MAJOR.$MINOR_$SECURITY"
-    if java_version.starts_with("1.") {
-        return java_version.split('.').nth(1).unwrap();
+    let major_version = if java_version.starts_with("1.") {
+        java_version.split('.').nth(1).unwrap()
+    } else {
+        java_version.split('.').next().unwrap()
+    };
+
+    // Get rid of the -ea, -internal, +35 (build), _ea, etc. suffix
+    major_version.split(['-', '+', '_']).next().unwrap()
+}
+
+/// A drilldown of the Java distribution (outer) and the major version (inner), based on
+/// the `javaVendor` and `javaVmName` properties.
+pub struct JavaVendorParser;
+
+impl Parser for JavaVendorParser {
+    fn parse(&self, context: &ParseContext) -> Option<Value> {
+        let get = |name: &str| context.schema.extra.get(name).and_then(|v| v.as_str());
+        let java_version = get("javaVersion")?;
+        let distribution = get_java_distribution(get("javaVendor"), get("javaVmName"));
+        Some(json!(DrilldownPie {
+            values: HashMap::from([(
+                distribution,
+                HashMap::from([(format!("Java {}", get_java_major_version(java_version)), 1),])
+            ),])
+        }))
     }
+}
 
-    java_version
-        .split('.')
-        .next()
-        .unwrap()
-        .split('-') // Get rid of the -ea, -internal, etc. suffix
-        .next()
-        .unwrap()
+/// Distributions that can be detected by their VM name, regardless of the vendor.
+const JAVA_VM_DISTRIBUTIONS: &[(&str, &str)] = &[("openj9", "OpenJ9"), ("graalvm", "GraalVM")];
+
+/// Distributions that can be detected by their vendor.
+/// The order is important here -> GraalVM before Oracle, as Oracle GraalVM has the vendor
+/// "Oracle Corporation" (GraalVM CE has "GraalVM Community").
+const JAVA_VENDOR_DISTRIBUTIONS: &[(&str, &str)] = &[
+    ("graalvm", "GraalVM"),
+    ("adoptium", "Temurin"),
+    ("temurin", "Temurin"),
+    ("adoptopenjdk", "AdoptOpenJDK"),
+    ("azul", "Zulu"),
+    ("amazon", "Corretto"),
+    ("bellsoft", "Liberica"),
+    ("microsoft", "Microsoft"),
+    ("sap se", "SapMachine"),
+    ("red hat", "Red Hat"),
+    ("alibaba", "Dragonwell"),
+    ("jetbrains", "JetBrains Runtime"),
+    ("international business machines", "IBM"),
+    ("ibm", "IBM"),
+    ("private build", "Ubuntu"),
+    ("debian", "Debian"),
+    ("homebrew", "Homebrew"),
+    ("oracle", "Oracle"),
+];
+
+fn get_java_distribution(java_vendor: Option<&str>, java_vm_name: Option<&str>) -> String {
+    let vm_name = java_vm_name.unwrap_or_default().to_ascii_lowercase();
+    let vendor = java_vendor.unwrap_or_default().trim();
+    let vendor_lower = vendor.to_ascii_lowercase();
+
+    JAVA_VM_DISTRIBUTIONS
+        .iter()
+        .find(|(needle, _)| vm_name.contains(needle))
+        .or_else(|| {
+            JAVA_VENDOR_DISTRIBUTIONS
+                .iter()
+                .find(|(needle, _)| vendor_lower.contains(needle))
+        })
+        .map(|(_, distribution)| distribution.to_string())
+        .unwrap_or_else(|| {
+            if vendor.is_empty() {
+                String::from("Unknown")
+            } else {
+                vendor.to_string()
+            }
+        })
 }
 
 #[cfg(test)]
@@ -52,6 +121,13 @@ mod tests {
         assert_eq!(get_java_major_version("21"), "21");
         assert_eq!(get_java_major_version("21-ea"), "21");
         assert_eq!(get_java_major_version("22.0.1"), "22");
+        assert_eq!(get_java_major_version("1.8.0_ea"), "8");
+        assert_eq!(get_java_major_version("1.8_ea"), "8");
+        assert_eq!(get_java_major_version("17+35"), "17");
+        assert_eq!(get_java_major_version("21.0.1+12-LTS"), "21");
+        assert_eq!(get_java_major_version("21+35-2513"), "21");
+        assert_eq!(get_java_major_version("17.0.12+7"), "17");
+        assert_eq!(get_java_major_version("23-ea+37"), "23");
 
         // We do not want garbage data to cause a panic. Garbage in, garbage out is fine though.
         assert!(std::panic::catch_unwind(|| get_java_major_version("garbage data")).is_ok());
@@ -60,4 +136,89 @@ mod tests {
         assert!(std::panic::catch_unwind(|| get_java_major_version("1.")).is_ok());
         assert!(std::panic::catch_unwind(|| get_java_major_version("9.")).is_ok());
     }
+
+    #[test]
+    fn test_get_java_distribution() {
+        struct TestCase {
+            name: String,
+            java_vendor: Option<String>,
+            java_vm_name: Option<String>,
+            expected: String,
+        }
+
+        let test_cases = vec![
+            TestCase {
+                name: String::from("Temurin"),
+                java_vendor: Some(String::from("Eclipse Adoptium")),
+                java_vm_name: Some(String::from("OpenJDK 64-Bit Server VM")),
+                expected: String::from("Temurin"),
+            },
+            TestCase {
+                name: String::from("GraalVM Community"),
+                java_vendor: Some(String::from("GraalVM Community")),
+                java_vm_name: Some(String::from("OpenJDK 64-Bit Server VM")),
+                expected: String::from("GraalVM"),
+            },
+            TestCase {
+                name: String::from("Oracle GraalVM"),
+                java_vendor: Some(String::from("Oracle Corporation")),
+                java_vm_name: Some(String::from("Oracle GraalVM 21.0.2+13.1")),
+                expected: String::from("GraalVM"),
+            },
+            TestCase {
+                name: String::from("Zulu"),
+                java_vendor: Some(String::from("Azul Systems, Inc.")),
+                java_vm_name: Some(String::from("OpenJDK 64-Bit Server VM")),
+                expected: String::from("Zulu"),
+            },
+            TestCase {
+                name: String::from("IBM Semeru (OpenJ9)"),
+                java_vendor: Some(String::from("IBM Corporation")),
+                java_vm_name: Some(String::from("Eclipse OpenJ9 VM")),
+                expected: String::from("OpenJ9"),
+            },
+            TestCase {
+                name: String::from("Corretto"),
+                java_vendor: Some(String::from("Amazon.com Inc.")),
+                java_vm_name: Some(String::from("OpenJDK 64-Bit Server VM")),
+                expected: String::from("Corretto"),
+            },
+            TestCase {
+                name: String::from("Oracle"),
+                java_vendor: Some(String::from("Oracle Corporation")),
+                java_vm_name: Some(String::from("Java HotSpot(TM) 64-Bit Server VM")),
+                expected: String::from("Oracle"),
+            },
+            TestCase {
+                name: String::from("Ubuntu package"),
+                java_vendor: Some(String::from("Private Build")),
+                java_vm_name: Some(String::from("OpenJDK 64-Bit Server VM")),
+                expected: String::from("Ubuntu"),
+            },
+            TestCase {
+                name: String::from("Unknown vendor"),
+                java_vendor: Some(String::from(" bStats JDK ")),
+                java_vm_name: None,
+                expected: String::from("bStats JDK"),
+            },
+            TestCase {
+                name: String::from("No vendor"),
+                java_vendor: None,
+                java_vm_name: None,
+                expected: String::from("Unknown"),
+            },
+        ];
+
+        for test_case in test_cases {
+            let result = get_java_distribution(
+                test_case.java_vendor.as_deref(),
+                test_case.java_vm_name.as_deref(),
+            );
+            assert_eq!(
+                result, test_case.expected,
+                "Wrong result for {}",
+                test_case.name
+            );
+        }
+    }
 }
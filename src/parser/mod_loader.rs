@@ -0,0 +1,100 @@
+use std::collections::HashMap;
+
+use serde_json::{json, Value};
+
+use crate::charts::drilldown_pie::DrilldownPie;
+
+use super::{ParseContext, Parser};
+
+pub struct ModLoaderParser;
+
+impl Parser for ModLoaderParser {
+    fn parse(&self, context: &ParseContext) -> Option<Value> {
+        let (loader, version) = parse_mod_loader(&context.schema.extra)?;
+        Some(json!(DrilldownPie {
+            values: HashMap::from([(loader, HashMap::from([(version, 1)]))])
+        }))
+    }
+}
+
+/// The mod loaders and the property that contains their version.
+///
+/// The order is important here -> Quilt before Fabric, as Quilt can also report the version
+/// of the Fabric loader it is compatible with.
+const MOD_LOADERS: &[(&str, &str)] = &[
+    ("neoForgeVersion", "NeoForge"),
+    ("forgeVersion", "Forge"),
+    ("quiltLoaderVersion", "Quilt"),
+    ("fabricLoaderVersion", "Fabric"),
+];
+
+fn parse_mod_loader(extra: &HashMap<String, Value>) -> Option<(String, String)> {
+    MOD_LOADERS.iter().find_map(|(property, loader)| {
+        extra
+            .get(*property)
+            .and_then(|v| v.as_str())
+            .map(|version| (loader.to_string(), version.to_string()))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_mod_loader() {
+        struct TestCase {
+            name: String,
+            extra: Value,
+            expected: Option<(String, String)>,
+        }
+
+        let test_cases = vec![
+            TestCase {
+                name: String::from("Fabric"),
+                extra: json!({ "fabricLoaderVersion": "0.16.5" }),
+                expected: Some((String::from("Fabric"), String::from("0.16.5"))),
+            },
+            TestCase {
+                name: String::from("Quilt with Fabric compatibility"),
+                extra: json!({
+                    "quiltLoaderVersion": "0.26.4",
+                    "fabricLoaderVersion": "0.15.11"
+                }),
+                expected: Some((String::from("Quilt"), String::from("0.26.4"))),
+            },
+            TestCase {
+                name: String::from("NeoForge"),
+                extra: json!({ "neoForgeVersion": "21.1.65" }),
+                expected: Some((String::from("NeoForge"), String::from("21.1.65"))),
+            },
+            TestCase {
+                name: String::from("Forge"),
+                extra: json!({ "forgeVersion": "47.3.0" }),
+                expected: Some((String::from("Forge"), String::from("47.3.0"))),
+            },
+            TestCase {
+                name: String::from("No mod loader"),
+                extra: json!({ "bukkitVersion": "1.21-38-1f5db50 (MC: 1.21)" }),
+                expected: None,
+            },
+            TestCase {
+                name: String::from("Invalid version type"),
+                extra: json!({ "fabricLoaderVersion": 16 }),
+                expected: None,
+            },
+        ];
+
+        for test_case in test_cases {
+            let extra: HashMap<String, Value> = serde_json::from_value(test_case.extra).unwrap();
+            let result = parse_mod_loader(&extra);
+            assert_eq!(
+                result, test_case.expected,
+                "Wrong result for {}",
+                test_case.name
+            );
+        }
+    }
+}
@@ -1,24 +1,178 @@
-use serde_json::Value;
+use std::collections::HashMap;
 
-use crate::submit_data_schema::SubmitDataSchema;
+use serde_json::Value;
 
-use super::Parser;
+use super::{
+    request_parser_config::Position,
+    transform::{self, Transform},
+    ParseContext, Parser,
+};
 
 pub struct NameInRequestParser {
+    /// The name of the property. Nested properties can be accessed with a dotted path
+    /// (e.g. `server.platform.version`) or a JSON Pointer (e.g. `/server/platform/version`).
     pub name_in_request: String,
-    pub position: String,
+    pub position: Position,
+    pub transforms: Vec<Transform>,
 }
 
 impl Parser for NameInRequestParser {
-    fn parse(&self, schema: &SubmitDataSchema) -> Option<Value> {
-        if self.position == "global" {
-            return schema.extra.get(&self.name_in_request).cloned();
-        }
+    fn parse(&self, context: &ParseContext) -> Option<Value> {
+        let value = match self.position {
+            Position::Global => lookup(&context.schema.extra, &self.name_in_request),
+            Position::Plugin => lookup(&context.schema.service.extra, &self.name_in_request),
+            Position::Both => lookup(&context.schema.service.extra, &self.name_in_request)
+                .or_else(|| lookup(&context.schema.extra, &self.name_in_request)),
+        }?;
+        transform::apply_all(&self.transforms, value.clone())
+    }
+}
+
+/// Looks up a property by its name, a dotted path or a JSON Pointer.
+pub(super) fn lookup<'a>(extra: &'a HashMap<String, Value>, name: &str) -> Option<&'a Value> {
+    if let Some(pointer) = name.strip_prefix('/') {
+        let (key, rest) = match pointer.find('/') {
+            Some(index) => pointer.split_at(index),
+            None => (pointer, ""),
+        };
+        let key = key.replace("~1", "/").replace("~0", "~");
+        return extra.get(&key)?.pointer(rest);
+    }
 
-        if self.position == "plugin" {
-            return schema.service.extra.get(&self.name_in_request).cloned();
+    // Top-level properties that contain a dot take precedence over nested properties
+    if let Some(value) = extra.get(name) {
+        return Some(value);
+    }
+    let mut segments = name.split('.');
+    let first = extra.get(segments.next()?)?;
+    segments.try_fold(first, |value, segment| match value {
+        Value::Object(object) => object.get(segment),
+        Value::Array(array) => array.get(segment.parse::<usize>().ok()?),
+        _ => None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::submit_data_schema::SubmitDataSchema;
+
+    #[test]
+    fn test_parse_name_in_request() {
+        struct TestCase {
+            name: String,
+            name_in_request: String,
+            position: Position,
+            expected: Option<Value>,
         }
 
-        return None;
+        let schema: SubmitDataSchema = serde_json::from_value(json!({
+            "serverUUID": "00000000-0000-0000-0000-000000000000",
+            "playerAmount": 5,
+            "dotted.key": "top-level",
+            "server": {
+                "platform": { "name": "Paper", "version": "1.21" },
+                "plugins": ["WorldEdit", "LuckPerms"],
+                "a/b": "slash"
+            },
+            "service": {
+                "id": 1,
+                "pluginVersion": "1.0.0",
+                "playerAmount": 3,
+                "database": { "type": "SQLite" }
+            }
+        }))
+        .unwrap();
+
+        let test_cases = vec![
+            TestCase {
+                name: String::from("Global top-level"),
+                name_in_request: String::from("playerAmount"),
+                position: Position::Global,
+                expected: Some(json!(5)),
+            },
+            TestCase {
+                name: String::from("Plugin top-level"),
+                name_in_request: String::from("pluginVersion"),
+                position: Position::Plugin,
+                expected: Some(json!("1.0.0")),
+            },
+            TestCase {
+                name: String::from("Dotted path"),
+                name_in_request: String::from("server.platform.version"),
+                position: Position::Global,
+                expected: Some(json!("1.21")),
+            },
+            TestCase {
+                name: String::from("Dotted path with array index"),
+                name_in_request: String::from("server.plugins.1"),
+                position: Position::Global,
+                expected: Some(json!("LuckPerms")),
+            },
+            TestCase {
+                name: String::from("Top-level key with dot"),
+                name_in_request: String::from("dotted.key"),
+                position: Position::Global,
+                expected: Some(json!("top-level")),
+            },
+            TestCase {
+                name: String::from("JSON Pointer"),
+                name_in_request: String::from("/server/platform/name"),
+                position: Position::Global,
+                expected: Some(json!("Paper")),
+            },
+            TestCase {
+                name: String::from("JSON Pointer with escaped slash"),
+                name_in_request: String::from("/server/a~1b"),
+                position: Position::Global,
+                expected: Some(json!("slash")),
+            },
+            TestCase {
+                name: String::from("JSON Pointer to top-level"),
+                name_in_request: String::from("/playerAmount"),
+                position: Position::Global,
+                expected: Some(json!(5)),
+            },
+            TestCase {
+                name: String::from("Missing nested property"),
+                name_in_request: String::from("server.platform.build"),
+                position: Position::Global,
+                expected: None,
+            },
+            TestCase {
+                name: String::from("Both prefers plugin"),
+                name_in_request: String::from("playerAmount"),
+                position: Position::Both,
+                expected: Some(json!(3)),
+            },
+            TestCase {
+                name: String::from("Both falls back to global"),
+                name_in_request: String::from("server.platform.name"),
+                position: Position::Both,
+                expected: Some(json!("Paper")),
+            },
+            TestCase {
+                name: String::from("Both with nested plugin property"),
+                name_in_request: String::from("database.type"),
+                position: Position::Both,
+                expected: Some(json!("SQLite")),
+            },
+        ];
+
+        for test_case in test_cases {
+            let parser = NameInRequestParser {
+                name_in_request: test_case.name_in_request,
+                position: test_case.position,
+                transforms: vec![],
+            };
+            assert_eq!(
+                parser.parse(&ParseContext::new(&schema)),
+                test_case.expected,
+                "Wrong result for {}",
+                test_case.name
+            );
+        }
     }
 }
@@ -0,0 +1,321 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use serde_with::skip_serializing_none;
+
+use crate::charts::simple_pie::SimplePie;
+
+use super::{registry, ParseContext, Parser};
+
+/// Puts a numeric property into ranges, e.g. 6 cores into "5-8".
+///
+/// It can be configured with the `field`, `buckets` (the inclusive upper bounds of all but the
+/// last range) and `labels` (one more than `buckets`) options, e.g.
+/// `{ "useHardcodedParser": "coreCount", "buckets": [2, 4], "labels": ["1-2", "3-4", "5+"] }`.
+/// Without `labels`, the labels are generated from the bounds (assuming integer values).
+#[derive(Debug, Clone, PartialEq)]
+pub struct NumericBucketParser {
+    pub field: String,
+    pub buckets: Buckets,
+}
+
+/// Ranges of numbers with a label for each range.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "RawBuckets", into = "RawBuckets")]
+pub struct Buckets {
+    /// The inclusive upper bounds of all ranges but the last one.
+    bounds: Vec<f64>,
+    labels: Vec<String>,
+}
+
+impl Buckets {
+    /// Creates the buckets for the given bounds. Without labels, they are generated from
+    /// the bounds (assuming integer values).
+    pub fn new(bounds: Vec<f64>, labels: Option<Vec<String>>) -> Result<Self, String> {
+        if bounds.is_empty() || bounds.windows(2).any(|w| w[0] >= w[1]) {
+            return Err(String::from(
+                "'buckets' must not be empty and strictly increasing",
+            ));
+        }
+        let labels = labels.unwrap_or_else(|| generate_labels(&bounds));
+        if labels.len() != bounds.len() + 1 {
+            return Err(format!(
+                "expected {} 'labels' for {} 'buckets'",
+                bounds.len() + 1,
+                bounds.len()
+            ));
+        }
+        Ok(Buckets { bounds, labels })
+    }
+
+    /// Returns the label of the range the value is in, or `None` for negative values.
+    pub fn get(&self, value: f64) -> Option<&str> {
+        if !value.is_finite() || value < 0.0 {
+            return None;
+        }
+        let index = self
+            .bounds
+            .iter()
+            .position(|bound| value <= *bound)
+            .unwrap_or(self.bounds.len());
+        Some(&self.labels[index])
+    }
+}
+
+impl NumericBucketParser {
+    /// The parser for `{ "useHardcodedParser": "coreCount" }`.
+    pub fn core_count(options: &Map<String, Value>) -> Result<Self, String> {
+        Self::from_options(options, "coreCount", &[1.0, 2.0, 4.0, 8.0, 16.0], None)
+    }
+
+    /// The parser for `{ "useHardcodedParser": "memory" }`. The memory is in megabytes.
+    pub fn memory(options: &Map<String, Value>) -> Result<Self, String> {
+        Self::from_options(
+            options,
+            "maxMemory",
+            &[1024.0, 2048.0, 4096.0, 8192.0, 16384.0, 32768.0],
+            Some(&[
+                "≤1 GB", "1-2 GB", "2-4 GB", "4-8 GB", "8-16 GB", "16-32 GB", "32+ GB",
+            ]),
+        )
+    }
+
+    fn from_options(
+        options: &Map<String, Value>,
+        default_field: &str,
+        default_bounds: &[f64],
+        default_labels: Option<&[&str]>,
+    ) -> Result<Self, String> {
+        registry::check_options(options, &["field", "buckets", "labels"])?;
+
+        let field = match options.get("field") {
+            None => default_field.to_string(),
+            Some(Value::String(field)) => field.clone(),
+            Some(_) => return Err(String::from("'field' must be a string")),
+        };
+
+        let bounds = match options.get("buckets") {
+            None => default_bounds.to_vec(),
+            Some(Value::Array(buckets)) => buckets
+                .iter()
+                .map(|bound| bound.as_f64().ok_or("'buckets' must only contain numbers"))
+                .collect::<Result<Vec<_>, _>>()?,
+            Some(_) => return Err(String::from("'buckets' must be an array")),
+        };
+        let labels = match options.get("labels") {
+            Some(Value::Array(labels)) => Some(
+                labels
+                    .iter()
+                    .map(|label| {
+                        label
+                            .as_str()
+                            .map(String::from)
+                            .ok_or("'labels' must only contain strings")
+                    })
+                    .collect::<Result<Vec<_>, _>>()?,
+            ),
+            Some(_) => return Err(String::from("'labels' must be an array")),
+            // The default labels only fit the default bounds
+            None if !options.contains_key("buckets") => {
+                default_labels.map(|labels| labels.iter().map(|l| l.to_string()).collect())
+            }
+            None => None,
+        };
+
+        Ok(NumericBucketParser {
+            field,
+            buckets: Buckets::new(bounds, labels)?,
+        })
+    }
+}
+
+/// The [`Buckets`] as they are stored, e.g. `{ "buckets": [1, 2], "labels": ["1", "2", "3+"] }`.
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize)]
+struct RawBuckets {
+    buckets: Vec<f64>,
+    labels: Option<Vec<String>>,
+}
+
+impl TryFrom<RawBuckets> for Buckets {
+    type Error = String;
+
+    fn try_from(raw: RawBuckets) -> Result<Self, Self::Error> {
+        Buckets::new(raw.buckets, raw.labels)
+    }
+}
+
+impl From<Buckets> for RawBuckets {
+    fn from(buckets: Buckets) -> Self {
+        RawBuckets {
+            buckets: buckets.bounds,
+            labels: Some(buckets.labels),
+        }
+    }
+}
+
+/// Generates labels like "1", "2", "3-4", "5+" for the bounds [1, 2, 4].
+fn generate_labels(bounds: &[f64]) -> Vec<String> {
+    let mut labels = Vec::with_capacity(bounds.len() + 1);
+    let mut lower = 0.0;
+    for bound in bounds {
+        if lower + 1.0 >= *bound {
+            labels.push(format!("{}", bound));
+        } else {
+            labels.push(format!("{}-{}", lower + 1.0, bound));
+        }
+        lower = *bound;
+    }
+    labels.push(format!("{}+", lower + 1.0));
+    labels
+}
+
+impl Parser for NumericBucketParser {
+    fn parse(&self, context: &ParseContext) -> Option<Value> {
+        let value = match context.schema.extra.get(&self.field)? {
+            Value::Number(n) => n.as_f64()?,
+            Value::String(s) => s.trim().parse().ok()?,
+            _ => return None,
+        };
+        Some(json!(SimplePie {
+            value: self.buckets.get(value)?.to_string()
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_get_bucket() {
+        struct TestCase {
+            name: String,
+            parser: NumericBucketParser,
+            value: f64,
+            expected: Option<String>,
+        }
+
+        let core_count = NumericBucketParser::core_count(&Map::new()).unwrap();
+        let memory = NumericBucketParser::memory(&Map::new()).unwrap();
+        let custom = NumericBucketParser::core_count(
+            json!({ "buckets": [2, 4], "labels": ["1-2", "3-4", "5+"] })
+                .as_object()
+                .unwrap(),
+        )
+        .unwrap();
+        let generated = NumericBucketParser::memory(
+            json!({ "field": "memory", "buckets": [10, 20] })
+                .as_object()
+                .unwrap(),
+        )
+        .unwrap();
+
+        let test_cases = vec![
+            TestCase {
+                name: String::from("1 core"),
+                parser: core_count.clone(),
+                value: 1.0,
+                expected: Some(String::from("1")),
+            },
+            TestCase {
+                name: String::from("2 cores"),
+                parser: core_count.clone(),
+                value: 2.0,
+                expected: Some(String::from("2")),
+            },
+            TestCase {
+                name: String::from("3 cores"),
+                parser: core_count.clone(),
+                value: 3.0,
+                expected: Some(String::from("3-4")),
+            },
+            TestCase {
+                name: String::from("6 cores"),
+                parser: core_count.clone(),
+                value: 6.0,
+                expected: Some(String::from("5-8")),
+            },
+            TestCase {
+                name: String::from("16 cores"),
+                parser: core_count.clone(),
+                value: 16.0,
+                expected: Some(String::from("9-16")),
+            },
+            TestCase {
+                name: String::from("128 cores"),
+                parser: core_count.clone(),
+                value: 128.0,
+                expected: Some(String::from("17+")),
+            },
+            TestCase {
+                name: String::from("Negative cores"),
+                parser: core_count,
+                value: -1.0,
+                expected: None,
+            },
+            TestCase {
+                name: String::from("1 GB memory"),
+                parser: memory.clone(),
+                value: 1024.0,
+                expected: Some(String::from("≤1 GB")),
+            },
+            TestCase {
+                name: String::from("6 GB memory"),
+                parser: memory.clone(),
+                value: 6144.0,
+                expected: Some(String::from("4-8 GB")),
+            },
+            TestCase {
+                name: String::from("64 GB memory"),
+                parser: memory,
+                value: 65536.0,
+                expected: Some(String::from("32+ GB")),
+            },
+            TestCase {
+                name: String::from("Custom labels"),
+                parser: custom,
+                value: 5.0,
+                expected: Some(String::from("5+")),
+            },
+            TestCase {
+                name: String::from("Generated labels"),
+                parser: generated,
+                value: 15.0,
+                expected: Some(String::from("11-20")),
+            },
+        ];
+
+        for test_case in test_cases {
+            let result = test_case.parser.buckets.get(test_case.value);
+            assert_eq!(
+                result.map(String::from),
+                test_case.expected,
+                "Wrong result for {}",
+                test_case.name
+            );
+        }
+    }
+
+    #[test]
+    fn test_invalid_options() {
+        let invalid_options = vec![
+            json!({ "field": 1 }),
+            json!({ "buckets": [] }),
+            json!({ "buckets": [4, 2] }),
+            json!({ "buckets": ["1"] }),
+            json!({ "buckets": [1, 2], "labels": ["1", "2+"] }),
+            json!({ "labels": "1" }),
+            json!({ "bukets": [2, 4] }),
+        ];
+
+        for options in invalid_options {
+            assert!(
+                NumericBucketParser::core_count(options.as_object().unwrap()).is_err(),
+                "Expected error for {}",
+                options
+            );
+        }
+    }
+}
@@ -1,17 +1,27 @@
 use std::collections::HashMap;
 
+use once_cell::sync::Lazy;
+use regex::Regex;
 use serde_json::{json, Value};
 
-use crate::{charts::drilldown_pie::DrilldownPie, submit_data_schema::SubmitDataSchema};
+use crate::charts::drilldown_pie::DrilldownPie;
 
-use super::Parser;
+use super::{architecture::normalize_architecture, ParseContext, Parser};
 
 pub struct OsParser;
 
 impl Parser for OsParser {
-    fn parse(&self, schema: &SubmitDataSchema) -> Option<Value> {
-        let os_name = schema.extra.get("osName").and_then(|v| v.as_str())?;
-        let os_version = schema.extra.get("osVersion").and_then(|v| v.as_str())?;
+    fn parse(&self, context: &ParseContext) -> Option<Value> {
+        let os_name = context
+            .schema
+            .extra
+            .get("osName")
+            .and_then(|v| v.as_str())?;
+        let os_version = context
+            .schema
+            .extra
+            .get("osVersion")
+            .and_then(|v| v.as_str())?;
         let (outer, inner) = parse_os(os_name, os_version);
         Some(json!(DrilldownPie {
             values: HashMap::from([(outer, HashMap::from([(inner, 1),])),])
@@ -19,6 +29,30 @@ impl Parser for OsParser {
     }
 }
 
+/// Like [`OsParser`], but groups Linux hosts by distribution family and includes the
+/// CPU architecture (`osArch`), e.g. "Debian" → "Ubuntu (aarch64)".
+///
+/// The optional `osDistribution` (the `ID` of `/etc/os-release`) and `osContainer`
+/// (e.g. "docker") properties are used as hints. Without them, the distribution is
+/// guessed from the kernel version.
+pub struct ExtendedOsParser;
+
+impl Parser for ExtendedOsParser {
+    fn parse(&self, context: &ParseContext) -> Option<Value> {
+        let get = |name: &str| context.schema.extra.get(name).and_then(|v| v.as_str());
+        let (outer, inner) = parse_extended_os(
+            get("osName")?,
+            get("osVersion")?,
+            get("osArch"),
+            get("osDistribution"),
+            get("osContainer"),
+        );
+        Some(json!(DrilldownPie {
+            values: HashMap::from([(outer, HashMap::from([(inner, 1),])),])
+        }))
+    }
+}
+
 fn parse_os(os_name: &str, os_version: &str) -> (String, String) {
     match os_name {
         // In Linux, the os name is "Linux" and the os version is the kernel version.
@@ -34,6 +68,101 @@ fn parse_os(os_name: &str, os_version: &str) -> (String, String) {
     }
 }
 
+fn parse_extended_os(
+    os_name: &str,
+    os_version: &str,
+    os_arch: Option<&str>,
+    distribution: Option<&str>,
+    container: Option<&str>,
+) -> (String, String) {
+    let (outer, mut inner) = if os_name.starts_with("Linux") {
+        let (family, mut name) = parse_linux_distribution(os_version, distribution);
+        if os_version.contains("microsoft") || os_version.contains("WSL") {
+            name = format!("{} on WSL", name);
+        }
+        if let Some(container) = container.filter(|c| !c.is_empty()) {
+            name = format!("{} in {}", name, container);
+        }
+        (family.to_string(), name)
+    } else {
+        parse_os(os_name, os_version)
+    };
+
+    if let Some(os_arch) = os_arch {
+        inner = format!("{} ({})", inner, normalize_architecture(os_arch));
+    }
+    (outer, inner)
+}
+
+/// The `ID`s of `/etc/os-release` with their distribution family and name.
+const LINUX_DISTRIBUTIONS: &[(&str, &str, &str)] = &[
+    ("ubuntu", "Debian", "Ubuntu"),
+    ("debian", "Debian", "Debian"),
+    ("raspbian", "Debian", "Raspberry Pi OS"),
+    ("linuxmint", "Debian", "Linux Mint"),
+    ("pop", "Debian", "Pop!_OS"),
+    ("rhel", "Red Hat", "RHEL"),
+    ("centos", "Red Hat", "CentOS"),
+    ("rocky", "Red Hat", "Rocky Linux"),
+    ("almalinux", "Red Hat", "AlmaLinux"),
+    ("fedora", "Red Hat", "Fedora"),
+    ("ol", "Red Hat", "Oracle Linux"),
+    ("amzn", "Red Hat", "Amazon Linux"),
+    ("alpine", "Alpine", "Alpine Linux"),
+    ("arch", "Arch", "Arch Linux"),
+    ("manjaro", "Arch", "Manjaro"),
+    ("opensuse", "SUSE", "openSUSE"),
+    ("opensuse-leap", "SUSE", "openSUSE"),
+    ("opensuse-tumbleweed", "SUSE", "openSUSE"),
+    ("sles", "SUSE", "SLES"),
+    ("gentoo", "Gentoo", "Gentoo"),
+    ("nixos", "NixOS", "NixOS"),
+];
+
+/// Patterns in the kernel version that are specific to a distribution.
+/// The order is important here -> Proxmox and Raspberry Pi OS before Debian.
+static LINUX_KERNEL_PATTERNS: Lazy<Vec<(Regex, &str, &str)>> = Lazy::new(|| {
+    [
+        (r"-pve$", "Debian", "Proxmox VE"),
+        (r"rpi", "Debian", "Raspberry Pi OS"),
+        (
+            r"-(generic|aws|azure|gcp|oracle|lowlatency|kvm)$",
+            "Debian",
+            "Ubuntu",
+        ),
+        (r"-\d+-(cloud-|rt-)?(amd64|arm64)$", "Debian", "Debian"),
+        (r"\.el\d+", "Red Hat", "Enterprise Linux"),
+        (r"\.fc\d+", "Red Hat", "Fedora"),
+        (r"amzn", "Red Hat", "Amazon Linux"),
+        (r"-arch\d", "Arch", "Arch Linux"),
+        (r"(?i)manjaro", "Arch", "Manjaro"),
+        (r"(?i)unraid", "Other Linux", "Unraid"),
+    ]
+    .into_iter()
+    .map(|(pattern, family, name)| (Regex::new(pattern).unwrap(), family, name))
+    .collect()
+});
+
+fn parse_linux_distribution(
+    kernel_version: &str,
+    distribution: Option<&str>,
+) -> (&'static str, String) {
+    if let Some(distribution) = distribution.map(str::trim).filter(|d| !d.is_empty()) {
+        let id = distribution.to_ascii_lowercase();
+        return LINUX_DISTRIBUTIONS
+            .iter()
+            .find(|(known_id, _, _)| *known_id == id)
+            .map(|(_, family, name)| (*family, name.to_string()))
+            .unwrap_or(("Other Linux", distribution.to_string()));
+    }
+
+    LINUX_KERNEL_PATTERNS
+        .iter()
+        .find(|(pattern, _, _)| pattern.is_match(kernel_version))
+        .map(|(_, family, name)| (*family, name.to_string()))
+        .unwrap_or(("Other Linux", String::from("Unknown")))
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -117,4 +246,173 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_parse_extended_os() {
+        struct TestCase {
+            name: String,
+            os_name: String,
+            os_version: String,
+            os_arch: Option<String>,
+            distribution: Option<String>,
+            container: Option<String>,
+            expected: (String, String),
+        }
+
+        let test_cases = vec![
+            TestCase {
+                name: String::from("Ubuntu with hint"),
+                os_name: String::from("Linux"),
+                os_version: String::from("5.15.0-105-generic"),
+                os_arch: Some(String::from("amd64")),
+                distribution: Some(String::from("ubuntu")),
+                container: None,
+                expected: (String::from("Debian"), String::from("Ubuntu (x86_64)")),
+            },
+            TestCase {
+                name: String::from("Ubuntu from kernel version"),
+                os_name: String::from("Linux"),
+                os_version: String::from("6.8.0-1012-aws"),
+                os_arch: Some(String::from("aarch64")),
+                distribution: None,
+                container: None,
+                expected: (String::from("Debian"), String::from("Ubuntu (aarch64)")),
+            },
+            TestCase {
+                name: String::from("Debian from kernel version"),
+                os_name: String::from("Linux"),
+                os_version: String::from("6.1.0-18-amd64"),
+                os_arch: Some(String::from("amd64")),
+                distribution: None,
+                container: None,
+                expected: (String::from("Debian"), String::from("Debian (x86_64)")),
+            },
+            TestCase {
+                name: String::from("Proxmox"),
+                os_name: String::from("Linux"),
+                os_version: String::from("6.8.4-2-pve"),
+                os_arch: Some(String::from("amd64")),
+                distribution: None,
+                container: None,
+                expected: (String::from("Debian"), String::from("Proxmox VE (x86_64)")),
+            },
+            TestCase {
+                name: String::from("Raspberry Pi"),
+                os_name: String::from("Linux"),
+                os_version: String::from("6.6.31+rpt-rpi-v8"),
+                os_arch: Some(String::from("aarch64")),
+                distribution: None,
+                container: None,
+                expected: (
+                    String::from("Debian"),
+                    String::from("Raspberry Pi OS (aarch64)"),
+                ),
+            },
+            TestCase {
+                name: String::from("Rocky Linux"),
+                os_name: String::from("Linux"),
+                os_version: String::from("5.14.0-362.24.1.el9_3.x86_64"),
+                os_arch: Some(String::from("amd64")),
+                distribution: Some(String::from("rocky")),
+                container: None,
+                expected: (
+                    String::from("Red Hat"),
+                    String::from("Rocky Linux (x86_64)"),
+                ),
+            },
+            TestCase {
+                name: String::from("Enterprise Linux from kernel version"),
+                os_name: String::from("Linux"),
+                os_version: String::from("5.14.0-362.24.1.el9_3.x86_64"),
+                os_arch: Some(String::from("amd64")),
+                distribution: None,
+                container: None,
+                expected: (
+                    String::from("Red Hat"),
+                    String::from("Enterprise Linux (x86_64)"),
+                ),
+            },
+            TestCase {
+                name: String::from("Alpine in Docker"),
+                os_name: String::from("Linux"),
+                os_version: String::from("6.1.0-18-amd64"),
+                os_arch: Some(String::from("amd64")),
+                distribution: Some(String::from("alpine")),
+                container: Some(String::from("docker")),
+                expected: (
+                    String::from("Alpine"),
+                    String::from("Alpine Linux in docker (x86_64)"),
+                ),
+            },
+            TestCase {
+                name: String::from("WSL2"),
+                os_name: String::from("Linux"),
+                os_version: String::from("5.15.153.1-microsoft-standard-WSL2"),
+                os_arch: Some(String::from("amd64")),
+                distribution: Some(String::from("ubuntu")),
+                container: None,
+                expected: (
+                    String::from("Debian"),
+                    String::from("Ubuntu on WSL (x86_64)"),
+                ),
+            },
+            TestCase {
+                name: String::from("Unknown distribution hint"),
+                os_name: String::from("Linux"),
+                os_version: String::from("6.6.0"),
+                os_arch: Some(String::from("amd64")),
+                distribution: Some(String::from("bstatsos")),
+                container: None,
+                expected: (
+                    String::from("Other Linux"),
+                    String::from("bstatsos (x86_64)"),
+                ),
+            },
+            TestCase {
+                name: String::from("Unknown distribution"),
+                os_name: String::from("Linux"),
+                os_version: String::from("6.6.0"),
+                os_arch: None,
+                distribution: None,
+                container: None,
+                expected: (String::from("Other Linux"), String::from("Unknown")),
+            },
+            TestCase {
+                name: String::from("Mac OS X on Apple Silicon"),
+                os_name: String::from("Mac OS X"),
+                os_version: String::from("14.5"),
+                os_arch: Some(String::from("aarch64")),
+                distribution: None,
+                container: None,
+                expected: (
+                    String::from("Mac OS X"),
+                    String::from("Mac OS X 14.5 (aarch64)"),
+                ),
+            },
+            TestCase {
+                name: String::from("Windows 11"),
+                os_name: String::from("Windows 11"),
+                os_version: String::from("10.0"),
+                os_arch: Some(String::from("amd64")),
+                distribution: None,
+                container: None,
+                expected: (String::from("Windows"), String::from("Windows 11 (x86_64)")),
+            },
+        ];
+
+        for test_case in test_cases {
+            let result = parse_extended_os(
+                &test_case.os_name,
+                &test_case.os_version,
+                test_case.os_arch.as_deref(),
+                test_case.distribution.as_deref(),
+                test_case.container.as_deref(),
+            );
+            assert_eq!(
+                result, test_case.expected,
+                "Wrong result for {}",
+                test_case.name
+            );
+        }
+    }
 }
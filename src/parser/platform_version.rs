@@ -0,0 +1,152 @@
+use std::collections::HashMap;
+
+use serde_json::{json, Map, Value};
+
+use crate::charts::drilldown_pie::DrilldownPie;
+
+use super::{registry, ParseContext, Parser};
+
+/// A drilldown of the platform (outer) and its version (inner) for platforms without a
+/// dedicated parser, e.g.
+/// `{ "useHardcodedParser": "platformVersion", "platformField": "serverName", "versionField": "serverVersion" }`.
+pub struct PlatformVersionParser {
+    /// The property that contains the version.
+    pub version_field: String,
+    /// The property that contains the platform name.
+    pub platform_field: Option<String>,
+    /// The platform name that is used if `platform_field` is not set or missing in the request.
+    pub platform: Option<String>,
+}
+
+impl PlatformVersionParser {
+    pub fn from_options(options: &Map<String, Value>) -> Result<Self, String> {
+        registry::check_options(options, &["versionField", "platformField", "platform"])?;
+
+        let get = |name: &str| -> Result<Option<String>, String> {
+            match options.get(name) {
+                None => Ok(None),
+                Some(Value::String(s)) => Ok(Some(s.clone())),
+                Some(_) => Err(format!("'{}' must be a string", name)),
+            }
+        };
+
+        let version_field = get("versionField")?.ok_or("missing 'versionField'")?;
+        let platform_field = get("platformField")?;
+        let platform = get("platform")?;
+        if platform_field.is_none() && platform.is_none() {
+            return Err(String::from("expected 'platformField' or 'platform'"));
+        }
+
+        Ok(PlatformVersionParser {
+            version_field,
+            platform_field,
+            platform,
+        })
+    }
+
+    fn parse_platform_version(&self, extra: &HashMap<String, Value>) -> Option<(String, String)> {
+        let version = extra.get(&self.version_field).and_then(|v| v.as_str())?;
+        let platform = self
+            .platform_field
+            .as_ref()
+            .and_then(|field| extra.get(field))
+            .and_then(|v| v.as_str())
+            .or(self.platform.as_deref())?;
+        Some((platform.to_string(), version.to_string()))
+    }
+}
+
+impl Parser for PlatformVersionParser {
+    fn parse(&self, context: &ParseContext) -> Option<Value> {
+        let (platform, version) = self.parse_platform_version(&context.schema.extra)?;
+        Some(json!(DrilldownPie {
+            values: HashMap::from([(platform, HashMap::from([(version, 1)]))])
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_platform_version() {
+        struct TestCase {
+            name: String,
+            options: Value,
+            extra: Value,
+            expected: Option<(String, String)>,
+        }
+
+        let test_cases = vec![
+            TestCase {
+                name: String::from("Platform field"),
+                options: json!({ "platformField": "serverName", "versionField": "serverVersion" }),
+                extra: json!({ "serverName": "Nukkit", "serverVersion": "1.0.0" }),
+                expected: Some((String::from("Nukkit"), String::from("1.0.0"))),
+            },
+            TestCase {
+                name: String::from("Fixed platform"),
+                options: json!({ "platform": "Minestom", "versionField": "minestomVersion" }),
+                extra: json!({ "minestomVersion": "2024.08.09" }),
+                expected: Some((String::from("Minestom"), String::from("2024.08.09"))),
+            },
+            TestCase {
+                name: String::from("Missing platform field uses fixed platform"),
+                options: json!({
+                    "platformField": "serverName",
+                    "platform": "Unknown",
+                    "versionField": "serverVersion"
+                }),
+                extra: json!({ "serverVersion": "1.0.0" }),
+                expected: Some((String::from("Unknown"), String::from("1.0.0"))),
+            },
+            TestCase {
+                name: String::from("Missing platform field"),
+                options: json!({ "platformField": "serverName", "versionField": "serverVersion" }),
+                extra: json!({ "serverVersion": "1.0.0" }),
+                expected: None,
+            },
+            TestCase {
+                name: String::from("Missing version"),
+                options: json!({ "platform": "Minestom", "versionField": "minestomVersion" }),
+                extra: json!({}),
+                expected: None,
+            },
+        ];
+
+        for test_case in test_cases {
+            let parser =
+                PlatformVersionParser::from_options(test_case.options.as_object().unwrap())
+                    .unwrap();
+            let extra: HashMap<String, Value> = serde_json::from_value(test_case.extra).unwrap();
+            assert_eq!(
+                parser.parse_platform_version(&extra),
+                test_case.expected,
+                "Wrong result for {}",
+                test_case.name
+            );
+        }
+    }
+
+    #[test]
+    fn test_invalid_options() {
+        let invalid_options = vec![
+            json!({}),
+            json!({ "platform": "Minestom" }),
+            json!({ "versionField": "minestomVersion" }),
+            json!({ "platform": 1, "versionField": "minestomVersion" }),
+            json!({ "platform": "Minestom", "versionFeld": "minestomVersion" }),
+        ];
+
+        for options in invalid_options {
+            assert!(
+                PlatformVersionParser::from_options(options.as_object().unwrap()).is_err(),
+                "Expected error for {}",
+                options
+            );
+        }
+    }
+}
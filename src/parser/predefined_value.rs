@@ -1,59 +1,254 @@
-use serde_json::{json, Value};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde_json::{Map, Value};
 
-use crate::submit_data_schema::SubmitDataSchema;
-
-use super::Parser;
+use super::{name_in_request::lookup, ParseContext, Parser};
 
+/// Uses a predefined value that can contain placeholders anywhere (in strings and
+/// object keys):
+///
+/// - `%country.iso%` and `%country.name%` of the server
+/// - `%metrics.version%` of the Metrics class
+/// - `%software.name%`, e.g. "Bukkit"
+/// - `%global.<key>%` and `%plugin.<key>%` for properties of the request (nested
+///   properties can be accessed with a dotted path)
+///
+/// A string that only consists of a placeholder is replaced with the value as it is
+/// (e.g. a number). If a placeholder cannot be resolved, no value is returned. Other
+/// text between percent signs is not changed.
 pub struct PredefinedValueParser {
     pub value: Value,
-    pub country_name: Option<String>,
 }
 
 impl Parser for PredefinedValueParser {
-    fn parse(&self, _schema: &SubmitDataSchema) -> Option<Value> {
-        self.parse()
+    fn parse(&self, context: &ParseContext) -> Option<Value> {
+        self.resolve(&self.value, context)
     }
 }
 
+/// Only the supported placeholders, so that other text between percent signs (e.g.
+/// "10%-20%") is kept as it is.
+static PLACEHOLDER_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"%(country\.(?:iso|name)|software\.name|metrics\.version|(?:global|plugin)\.[\w.\-]+)%",
+    )
+    .unwrap()
+});
+
 impl PredefinedValueParser {
-    fn parse(&self) -> Option<Value> {
-        if self
-            .value
-            .as_str()
-            .map(|s| s.eq("%country.name%"))
-            .unwrap_or(false)
-        {
-            let country_name = self.country_name.as_ref()?;
-            return Some(json!(country_name));
+    fn resolve(&self, value: &Value, context: &ParseContext) -> Option<Value> {
+        match value {
+            Value::String(s) => self.resolve_string(s, context),
+            Value::Array(array) => array
+                .iter()
+                .map(|value| self.resolve(value, context))
+                .collect::<Option<Vec<_>>>()
+                .map(Value::Array),
+            Value::Object(object) => object
+                .iter()
+                .map(|(key, value)| {
+                    let key = match self.resolve_string(key, context)? {
+                        Value::String(key) => key,
+                        key => to_string(&key)?,
+                    };
+                    Some((key, self.resolve(value, context)?))
+                })
+                .collect::<Option<Map<_, _>>>()
+                .map(Value::Object),
+            value => Some(value.clone()),
+        }
+    }
+
+    fn resolve_string(&self, s: &str, context: &ParseContext) -> Option<Value> {
+        // A string that is a single placeholder keeps the type of the value
+        if let Some(captures) = PLACEHOLDER_RE.captures(s) {
+            if captures[0].len() == s.len() {
+                return self.resolve_placeholder(&captures[1], context);
+            }
+        }
+
+        let mut result = String::with_capacity(s.len());
+        let mut last_end = 0;
+        for captures in PLACEHOLDER_RE.captures_iter(s) {
+            let placeholder = captures.get(0).unwrap();
+            result.push_str(&s[last_end..placeholder.start()]);
+            result.push_str(&to_string(
+                &self.resolve_placeholder(&captures[1], context)?,
+            )?);
+            last_end = placeholder.end();
         }
-        return Some(self.value.clone());
+        result.push_str(&s[last_end..]);
+        Some(Value::String(result))
+    }
+
+    fn resolve_placeholder(&self, placeholder: &str, context: &ParseContext) -> Option<Value> {
+        let string = |s: Option<&str>| s.map(|s| Value::String(s.to_string()));
+        let schema = context.schema;
+        match placeholder {
+            "country.iso" => string(context.country_iso),
+            "country.name" => string(context.country_name),
+            "software.name" => string(context.software.map(|s| s.name.as_str())),
+            "metrics.version" => string(schema.metrics_version.as_deref()),
+            _ => {
+                if let Some(key) = placeholder.strip_prefix("global.") {
+                    lookup(&schema.extra, key).cloned()
+                } else if let Some(key) = placeholder.strip_prefix("plugin.") {
+                    lookup(&schema.service.extra, key).cloned()
+                } else {
+                    None
+                }
+            }
+        }
+    }
+}
+
+/// The string representation of a value that is used inside of a string.
+fn to_string(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        _ => None,
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use serde_json::json;
 
     use super::*;
+    use crate::{software::Software, submit_data_schema::SubmitDataSchema};
+
+    fn test_schema() -> SubmitDataSchema {
+        serde_json::from_value(json!({
+            "serverUUID": "00000000-0000-0000-0000-000000000000",
+            "metricsVersion": "3.0.2",
+            "playerAmount": 5,
+            "server": { "platform": "Paper" },
+            "service": { "id": 1, "pluginVersion": "1.0.0", "database": ["SQLite"] }
+        }))
+        .unwrap()
+    }
 
     #[test]
     fn test_parse_os() {
+        let schema = test_schema();
+        let context = ParseContext {
+            country_name: Some("Germany"),
+            ..ParseContext::new(&schema)
+        };
         let parser = PredefinedValueParser {
             value: json!("%country.name%"),
-            country_name: Some(String::from("Germany")),
         };
 
-        let result = parser.parse();
+        let result = parser.parse(&context);
         assert_eq!(result.unwrap().as_str(), Some("Germany"));
 
         let parser = PredefinedValueParser {
             value: json!({"key": "value"}),
-            country_name: None,
         };
 
-        let result = parser.parse();
+        let result = parser.parse(&ParseContext::new(&schema));
         assert_eq!(
             result.unwrap().as_object(),
             Some(json!({"key": "value"}).as_object().unwrap())
         );
     }
+
+    #[test]
+    fn test_resolve_placeholders() {
+        struct TestCase {
+            name: String,
+            value: Value,
+            expected: Option<Value>,
+        }
+
+        let test_cases = vec![
+            TestCase {
+                name: String::from("Country iso"),
+                value: json!({ "value": "%country.iso%" }),
+                expected: Some(json!({ "value": "DE" })),
+            },
+            TestCase {
+                name: String::from("Metrics version and software name"),
+                value: json!("%software.name% (%metrics.version%)"),
+                expected: Some(json!("Bukkit (3.0.2)")),
+            },
+            TestCase {
+                name: String::from("Global property keeps its type"),
+                value: json!({ "value": "%global.playerAmount%" }),
+                expected: Some(json!({ "value": 5 })),
+            },
+            TestCase {
+                name: String::from("Nested global property"),
+                value: json!("%global.server.platform%"),
+                expected: Some(json!("Paper")),
+            },
+            TestCase {
+                name: String::from("Plugin property in drilldown key"),
+                value: json!({ "%software.name%": { "%plugin.pluginVersion%": 1 } }),
+                expected: Some(json!({ "Bukkit": { "1.0.0": 1 } })),
+            },
+            TestCase {
+                name: String::from("Placeholders in arrays"),
+                value: json!(["%country.name%", 1]),
+                expected: Some(json!(["Germany", 1])),
+            },
+            TestCase {
+                name: String::from("Text without placeholders"),
+                value: json!("50% of 100%"),
+                expected: Some(json!("50% of 100%")),
+            },
+            TestCase {
+                name: String::from("Text between percent signs"),
+                value: json!("10%-20%"),
+                expected: Some(json!("10%-20%")),
+            },
+            TestCase {
+                name: String::from("Unknown placeholder"),
+                value: json!({ "value": "%foo.bar%", "%done%": "100%_done%" }),
+                expected: Some(json!({ "value": "%foo.bar%", "%done%": "100%_done%" })),
+            },
+            TestCase {
+                name: String::from("Missing property"),
+                value: json!({ "value": "%plugin.missing%" }),
+                expected: None,
+            },
+            TestCase {
+                name: String::from("Array property inside of text"),
+                value: json!("Database: %plugin.database%"),
+                expected: None,
+            },
+        ];
+
+        let schema = test_schema();
+        let software = Software {
+            id: 1,
+            name: String::from("Bukkit"),
+            url: String::from("bukkit"),
+            global_plugin: None,
+            metrics_class: None,
+            example_plugin: None,
+            max_requests_per_ip: 10,
+            default_charts: vec![],
+            hide_in_plugin_list: false,
+        };
+        let context = ParseContext {
+            country_iso: Some("DE"),
+            country_name: Some("Germany"),
+            software: Some(&software),
+            ..ParseContext::new(&schema)
+        };
+        for test_case in test_cases {
+            let parser = PredefinedValueParser {
+                value: test_case.value,
+            };
+            assert_eq!(
+                parser.parse(&context),
+                test_case.expected,
+                "Wrong result for {}",
+                test_case.name
+            );
+        }
+    }
 }
@@ -0,0 +1,200 @@
+use std::{collections::HashMap, sync::RwLock};
+
+use once_cell::sync::Lazy;
+use serde_json::{Map, Value};
+
+use super::{
+    architecture, bukkit_minecraft_version, bukkit_server_software, bungeecord_version,
+    java_version, mod_loader, numeric_bucket, os, platform_version, sponge_version,
+    velocity_version, Parser,
+};
+
+/// Creates a hardcoded parser from the remaining properties of its `requestParser` block.
+///
+/// Returning an error marks the `requestParser` config as invalid.
+pub type ParserConstructor =
+    Box<dyn Fn(&Map<String, Value>) -> Result<Box<dyn Parser>, String> + Send + Sync>;
+
+/// Checks that the options only contain the given properties, so that a typo in the
+/// `requestParser` block is not silently ignored.
+pub fn check_options(options: &Map<String, Value>, known: &[&str]) -> Result<(), String> {
+    match options.keys().find(|key| !known.contains(&key.as_str())) {
+        Some(key) => Err(format!("unknown option '{}'", key)),
+        None => Ok(()),
+    }
+}
+
+/// A constructor for a parser that does not have any options.
+fn without_options(create: fn() -> Box<dyn Parser>) -> ParserConstructor {
+    Box::new(move |options| {
+        check_options(options, &[])?;
+        Ok(create())
+    })
+}
+
+static HARDCODED_PARSERS: Lazy<RwLock<HashMap<String, ParserConstructor>>> = Lazy::new(|| {
+    let mut parsers: HashMap<String, ParserConstructor> = HashMap::new();
+    parsers.insert(
+        String::from("os"),
+        without_options(|| Box::new(os::OsParser)),
+    );
+    parsers.insert(
+        String::from("osExtended"),
+        without_options(|| Box::new(os::ExtendedOsParser)),
+    );
+    parsers.insert(
+        String::from("osArch"),
+        without_options(|| Box::new(architecture::ArchitectureParser)),
+    );
+    parsers.insert(
+        String::from("javaVersion"),
+        without_options(|| Box::new(java_version::JavaVersionParser)),
+    );
+    parsers.insert(
+        String::from("javaVendor"),
+        without_options(|| Box::new(java_version::JavaVendorParser)),
+    );
+    parsers.insert(
+        String::from("bukkitMinecraftVersion"),
+        without_options(|| Box::new(bukkit_minecraft_version::BukkitMinecraftVersionParser)),
+    );
+    parsers.insert(
+        String::from("bukkitMinecraftVersionDrilldown"),
+        without_options(|| {
+            Box::new(bukkit_minecraft_version::BukkitMinecraftVersionDrilldownParser)
+        }),
+    );
+    parsers.insert(
+        String::from("bukkitServerSoftware"),
+        without_options(|| Box::new(bukkit_server_software::BukkitServerSoftwareParser)),
+    );
+    parsers.insert(
+        String::from("bungeecordVersion"),
+        without_options(|| Box::new(bungeecord_version::BungeecordVersionParser)),
+    );
+    parsers.insert(
+        String::from("velocityVersion"),
+        without_options(|| Box::new(velocity_version::VelocityVersionParser)),
+    );
+    parsers.insert(
+        String::from("spongeVersion"),
+        without_options(|| Box::new(sponge_version::SpongeVersionParser)),
+    );
+    parsers.insert(
+        String::from("modLoader"),
+        without_options(|| Box::new(mod_loader::ModLoaderParser)),
+    );
+    parsers.insert(
+        String::from("platformVersion"),
+        Box::new(|options| {
+            Ok(Box::new(
+                platform_version::PlatformVersionParser::from_options(options)?,
+            ))
+        }),
+    );
+    parsers.insert(
+        String::from("coreCount"),
+        Box::new(|options| {
+            Ok(Box::new(numeric_bucket::NumericBucketParser::core_count(
+                options,
+            )?))
+        }),
+    );
+    parsers.insert(
+        String::from("memory"),
+        Box::new(|options| {
+            Ok(Box::new(numeric_bucket::NumericBucketParser::memory(
+                options,
+            )?))
+        }),
+    );
+    RwLock::new(parsers)
+});
+
+/// Registers a parser that can be used with `{ "useHardcodedParser": "<name>" }`.
+///
+/// This must be called before the first software is loaded (i.e. before the server is
+/// started). The parsers of default charts are created when the software is loaded, so
+/// every default chart that references a parser that is not registered yet fails to
+/// load. Registering a parser with the name of an existing parser replaces it.
+pub fn register_hardcoded_parser<F>(name: &str, constructor: F)
+where
+    F: Fn(&Map<String, Value>) -> Result<Box<dyn Parser>, String> + Send + Sync + 'static,
+{
+    HARDCODED_PARSERS
+        .write()
+        .unwrap()
+        .insert(String::from(name), Box::new(constructor));
+}
+
+/// Whether a hardcoded parser with the given name is registered.
+pub fn has_hardcoded_parser(name: &str) -> bool {
+    HARDCODED_PARSERS.read().unwrap().contains_key(name)
+}
+
+/// Creates the hardcoded parser with the given name and options.
+pub fn create_hardcoded_parser(
+    name: &str,
+    options: &Map<String, Value>,
+) -> Result<Box<dyn Parser>, String> {
+    let parsers = HARDCODED_PARSERS.read().unwrap();
+    let constructor = parsers
+        .get(name)
+        .ok_or_else(|| format!("unknown hardcoded parser '{}'", name))?;
+    constructor(options)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::{parser::ParseContext, submit_data_schema::SubmitDataSchema};
+
+    struct OptionParser {
+        value: Value,
+    }
+
+    impl Parser for OptionParser {
+        fn parse(&self, _context: &ParseContext) -> Option<Value> {
+            Some(self.value.clone())
+        }
+    }
+
+    #[test]
+    fn test_register_hardcoded_parser() {
+        register_hardcoded_parser("testOptionParser", |options| {
+            check_options(options, &["value"])?;
+            let value = options
+                .get("value")
+                .cloned()
+                .ok_or_else(|| String::from("missing 'value'"))?;
+            Ok(Box::new(OptionParser { value }))
+        });
+
+        let schema: SubmitDataSchema = serde_json::from_value(json!({
+            "playerAmount": 0,
+            "osName": "Linux",
+            "osArch": "amd64",
+            "osVersion": "5.10",
+            "coreCount": 4,
+            "service": { "id": 1, "customCharts": [] },
+            "serverUUID": "00000000-0000-0000-0000-000000000000",
+            "metricsVersion": "3.0.0"
+        }))
+        .unwrap();
+
+        let options = json!({ "value": "foo" }).as_object().unwrap().clone();
+        let parser = create_hardcoded_parser("testOptionParser", &options).unwrap();
+        assert_eq!(
+            parser.parse(&ParseContext::new(&schema)),
+            Some(json!("foo"))
+        );
+
+        assert!(create_hardcoded_parser("testOptionParser", &Map::new()).is_err());
+        assert!(create_hardcoded_parser("os", &Map::new()).is_ok());
+        let options = json!({ "foo": "bar" }).as_object().unwrap().clone();
+        assert!(create_hardcoded_parser("os", &options).is_err());
+        assert!(create_hardcoded_parser("unknownParser", &Map::new()).is_err());
+    }
+}
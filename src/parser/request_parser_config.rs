@@ -0,0 +1,320 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use serde_with::skip_serializing_none;
+
+use super::{registry, transform::Transform};
+
+/// The configuration of how the data for a default chart is extracted from a
+/// request (the `requestParser` of a [`DefaultChartTemplate`]).
+///
+/// [`DefaultChartTemplate`]: crate::charts::chart::DefaultChartTemplate
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "RawRequestParserConfig", into = "RawRequestParserConfig")]
+pub enum RequestParserConfig {
+    /// Always uses the same value, e.g. `{ "predefinedValue": 1 }`.
+    PredefinedValue { value: Value },
+    /// Uses a parser from the [registry](super::registry), e.g.
+    /// `{ "useHardcodedParser": "os", "position": "global" }`.
+    ///
+    /// All other properties are passed to the parser as `options`. The parser rejects
+    /// options it does not know.
+    HardcodedParser {
+        name: String,
+        position: Option<Position>,
+        options: Map<String, Value>,
+    },
+    /// Uses the value of a property of the request, e.g.
+    /// `{ "nameInRequest": "pluginVersion", "position": "plugin" }`.
+    ///
+    /// The value is transformed by the optional `transforms` (in order).
+    NameInRequest {
+        name: String,
+        position: Position,
+        transforms: Vec<Transform>,
+    },
+}
+
+/// Where in the request a property is located.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Position {
+    /// A property of the server (e.g. `osName`).
+    Global,
+    /// A property of the service (e.g. `pluginVersion`).
+    Plugin,
+    /// A property of the service or, if the service does not have it, of the server.
+    Both,
+}
+
+/// The `requestParser` JSON as it is stored.
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize)]
+struct RawRequestParserConfig {
+    #[serde(rename = "predefinedValue")]
+    predefined_value: Option<Value>,
+    #[serde(rename = "useHardcodedParser")]
+    use_hardcoded_parser: Option<String>,
+    #[serde(rename = "nameInRequest")]
+    name_in_request: Option<String>,
+    position: Option<Position>,
+    transforms: Option<Vec<Transform>>,
+    // The options of hardcoded parsers, or legacy properties like "type"
+    #[serde(flatten)]
+    other: Map<String, Value>,
+}
+
+/// Properties of old `requestParser` configs that are not used (anymore), but are
+/// still allowed for `predefinedValue` and `nameInRequest`.
+const LEGACY_PROPERTIES: [&str; 3] = ["type", "trueValue", "falseValue"];
+
+fn check_legacy_properties(other: &Map<String, Value>) -> Result<(), String> {
+    match other
+        .keys()
+        .find(|key| !LEGACY_PROPERTIES.contains(&key.as_str()))
+    {
+        Some(key) => Err(format!("unknown property '{}'", key)),
+        None => Ok(()),
+    }
+}
+
+impl TryFrom<RawRequestParserConfig> for RequestParserConfig {
+    type Error = String;
+
+    fn try_from(raw: RawRequestParserConfig) -> Result<Self, Self::Error> {
+        let kinds = [
+            raw.predefined_value.is_some(),
+            raw.use_hardcoded_parser.is_some(),
+            raw.name_in_request.is_some(),
+        ];
+        if kinds.iter().filter(|set| **set).count() > 1 {
+            return Err(String::from(
+                "only one of 'predefinedValue', 'useHardcodedParser' or 'nameInRequest' can be used",
+            ));
+        }
+
+        if raw.transforms.is_some() && raw.name_in_request.is_none() {
+            return Err(String::from(
+                "'transforms' can only be used with 'nameInRequest'",
+            ));
+        }
+
+        if let Some(value) = raw.predefined_value {
+            check_legacy_properties(&raw.other)?;
+            if raw.position.is_some() {
+                return Err(String::from(
+                    "'position' can not be used with 'predefinedValue'",
+                ));
+            }
+            return Ok(RequestParserConfig::PredefinedValue { value });
+        }
+
+        if let Some(name) = raw.use_hardcoded_parser {
+            // The options are checked when the parser is created for the template
+            if !registry::has_hardcoded_parser(&name) {
+                return Err(format!("unknown hardcoded parser '{}'", name));
+            }
+            return Ok(RequestParserConfig::HardcodedParser {
+                name,
+                position: raw.position,
+                options: raw.other,
+            });
+        }
+
+        if let Some(name) = raw.name_in_request {
+            check_legacy_properties(&raw.other)?;
+            let position = raw
+                .position
+                .ok_or_else(|| format!("missing 'position' for nameInRequest '{}'", name))?;
+            return Ok(RequestParserConfig::NameInRequest {
+                name,
+                position,
+                transforms: raw.transforms.unwrap_or_default(),
+            });
+        }
+
+        Err(String::from(
+            "expected one of 'predefinedValue', 'useHardcodedParser' or 'nameInRequest'",
+        ))
+    }
+}
+
+impl From<RequestParserConfig> for RawRequestParserConfig {
+    fn from(config: RequestParserConfig) -> Self {
+        let mut raw = RawRequestParserConfig {
+            predefined_value: None,
+            use_hardcoded_parser: None,
+            name_in_request: None,
+            position: None,
+            transforms: None,
+            other: Map::new(),
+        };
+        match config {
+            RequestParserConfig::PredefinedValue { value } => raw.predefined_value = Some(value),
+            RequestParserConfig::HardcodedParser {
+                name,
+                position,
+                options,
+            } => {
+                raw.use_hardcoded_parser = Some(name);
+                raw.position = position;
+                raw.other = options;
+            }
+            RequestParserConfig::NameInRequest {
+                name,
+                position,
+                transforms,
+            } => {
+                raw.name_in_request = Some(name);
+                raw.position = Some(position);
+                raw.transforms = Some(transforms).filter(|t| !t.is_empty());
+            }
+        }
+        raw
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_deserialize_request_parser_config() {
+        struct TestCase {
+            name: String,
+            json: Value,
+            expected: Option<RequestParserConfig>,
+        }
+
+        let test_cases = vec![
+            TestCase {
+                name: String::from("Predefined value"),
+                json: json!({ "predefinedValue": "%country.name%" }),
+                expected: Some(RequestParserConfig::PredefinedValue {
+                    value: json!("%country.name%"),
+                }),
+            },
+            TestCase {
+                name: String::from("Hardcoded parser"),
+                json: json!({ "useHardcodedParser": "os", "position": "global" }),
+                expected: Some(RequestParserConfig::HardcodedParser {
+                    name: String::from("os"),
+                    position: Some(Position::Global),
+                    options: Map::new(),
+                }),
+            },
+            TestCase {
+                name: String::from("Name in request with legacy properties"),
+                json: json!({
+                    "nameInRequest": "playerAmount",
+                    "type": "number",
+                    "position": "global"
+                }),
+                expected: Some(RequestParserConfig::NameInRequest {
+                    name: String::from("playerAmount"),
+                    position: Position::Global,
+                    transforms: vec![],
+                }),
+            },
+            TestCase {
+                name: String::from("Name in request with transforms"),
+                json: json!({
+                    "nameInRequest": "database",
+                    "position": "plugin",
+                    "transforms": [{ "type": "trim" }, { "type": "truncate", "maxLength": 10 }]
+                }),
+                expected: Some(RequestParserConfig::NameInRequest {
+                    name: String::from("database"),
+                    position: Position::Plugin,
+                    transforms: vec![Transform::Trim, Transform::Truncate { max_length: 10 }],
+                }),
+            },
+            TestCase {
+                name: String::from("Invalid transform"),
+                json: json!({
+                    "nameInRequest": "database",
+                    "position": "plugin",
+                    "transforms": [{ "type": "regex", "pattern": "(" }]
+                }),
+                expected: None,
+            },
+            TestCase {
+                name: String::from("Transforms for hardcoded parser"),
+                json: json!({ "useHardcodedParser": "os", "transforms": [{ "type": "trim" }] }),
+                expected: None,
+            },
+            TestCase {
+                name: String::from("Unknown hardcoded parser"),
+                json: json!({ "useHardcodedParser": "osss" }),
+                expected: None,
+            },
+            TestCase {
+                name: String::from("Name in request without position"),
+                json: json!({ "nameInRequest": "pluginVersion" }),
+                expected: None,
+            },
+            TestCase {
+                name: String::from("Name in request with nested path"),
+                json: json!({ "nameInRequest": "server.platform.version", "position": "both" }),
+                expected: Some(RequestParserConfig::NameInRequest {
+                    name: String::from("server.platform.version"),
+                    position: Position::Both,
+                    transforms: vec![],
+                }),
+            },
+            TestCase {
+                name: String::from("Invalid position"),
+                json: json!({ "nameInRequest": "pluginVersion", "position": "service" }),
+                expected: None,
+            },
+            TestCase {
+                name: String::from("Name in request with unknown property"),
+                json: json!({
+                    "nameInRequest": "database",
+                    "position": "global",
+                    "transfroms": [{ "type": "trim" }]
+                }),
+                expected: None,
+            },
+            TestCase {
+                name: String::from("Predefined value with unknown property"),
+                json: json!({ "predefinedValue": 1, "positon": "plugin" }),
+                expected: None,
+            },
+            TestCase {
+                name: String::from("Predefined value and name in request"),
+                json: json!({
+                    "predefinedValue": 1,
+                    "nameInRequest": "pluginVersion",
+                    "position": "plugin"
+                }),
+                expected: None,
+            },
+            TestCase {
+                name: String::from("Hardcoded parser and name in request"),
+                json: json!({
+                    "useHardcodedParser": "os",
+                    "nameInRequest": "osName",
+                    "position": "global"
+                }),
+                expected: None,
+            },
+            TestCase {
+                name: String::from("Typo"),
+                json: json!({ "nameInReqest": "pluginVersion", "position": "plugin" }),
+                expected: None,
+            },
+        ];
+
+        for test_case in test_cases {
+            let result = serde_json::from_value::<RequestParserConfig>(test_case.json);
+            assert_eq!(
+                result.ok(),
+                test_case.expected,
+                "Wrong result for {}",
+                test_case.name
+            );
+        }
+    }
+}
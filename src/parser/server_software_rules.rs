@@ -0,0 +1,293 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use once_cell::sync::Lazy;
+use redis::AsyncCommands;
+use regex::Regex;
+use serde::Deserialize;
+
+use crate::{config::ServerSoftwareRulesConfig, util::redis::RedisPool};
+
+/// Server software detection rules that are checked before the compiled-in brand table.
+///
+/// The rules can be changed at runtime, see [`spawn_rules_reloader`].
+static RULES: Lazy<RwLock<Arc<Vec<ServerSoftwareRule>>>> =
+    Lazy::new(|| RwLock::new(Arc::new(Vec::new())));
+
+/// The error of the last attempt to load the rules, if it failed.
+static LOAD_ERROR: Lazy<RwLock<Option<String>>> = Lazy::new(|| RwLock::new(None));
+
+/// A rule that maps a server to its software name.
+#[derive(Debug, Clone)]
+pub struct ServerSoftwareRule {
+    /// The software name that is used if the rule matches, e.g. "Paper".
+    pub name: String,
+    pub field: RuleField,
+    pub matcher: RuleMatcher,
+}
+
+/// The property of the request that a rule is matched against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum RuleField {
+    #[serde(rename = "bukkitVersion")]
+    BukkitVersion,
+    #[serde(rename = "bukkitName")]
+    BukkitName,
+}
+
+#[derive(Debug, Clone)]
+pub enum RuleMatcher {
+    /// Case-insensitive substring match. The string must be lowercase.
+    Contains(String),
+    /// Case-insensitive equality. The string must be lowercase.
+    Equals(String),
+    Regex(Regex),
+}
+
+impl ServerSoftwareRule {
+    fn matches(&self, bukkit_version: &str, bukkit_name: Option<&str>) -> bool {
+        let value = match self.field {
+            RuleField::BukkitVersion => bukkit_version,
+            RuleField::BukkitName => match bukkit_name {
+                Some(bukkit_name) => bukkit_name,
+                None => return false,
+            },
+        };
+        match &self.matcher {
+            RuleMatcher::Contains(s) => value.to_ascii_lowercase().contains(s.as_str()),
+            RuleMatcher::Equals(s) => value.eq_ignore_ascii_case(s),
+            RuleMatcher::Regex(regex) => regex.is_match(value),
+        }
+    }
+}
+
+/// The rules file (or Redis value) as it is stored, e.g.
+/// `{ "rules": [{ "name": "Paper", "field": "bukkitVersion", "contains": "paper" }] }`.
+#[derive(Deserialize)]
+struct RawRules {
+    rules: Vec<RawRule>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawRule {
+    name: String,
+    field: RuleField,
+    contains: Option<String>,
+    equals: Option<String>,
+    regex: Option<String>,
+}
+
+impl TryFrom<RawRule> for ServerSoftwareRule {
+    type Error = String;
+
+    fn try_from(raw: RawRule) -> Result<Self, Self::Error> {
+        let matcher = match (raw.contains, raw.equals, raw.regex) {
+            (Some(s), None, None) => RuleMatcher::Contains(s.to_ascii_lowercase()),
+            (None, Some(s), None) => RuleMatcher::Equals(s.to_ascii_lowercase()),
+            (None, None, Some(regex)) => RuleMatcher::Regex(
+                Regex::new(&regex)
+                    .map_err(|e| format!("invalid regex for rule '{}': {}", raw.name, e))?,
+            ),
+            _ => {
+                return Err(format!(
+                    "rule '{}' must have exactly one of 'contains', 'equals' or 'regex'",
+                    raw.name
+                ))
+            }
+        };
+        Ok(ServerSoftwareRule {
+            name: raw.name,
+            field: raw.field,
+            matcher,
+        })
+    }
+}
+
+/// Parses the rules from a JSON string.
+pub fn parse_json_rules(json: &str) -> Result<Vec<ServerSoftwareRule>, String> {
+    let raw: RawRules = serde_json::from_str(json).map_err(|e| e.to_string())?;
+    raw.rules.into_iter().map(TryFrom::try_from).collect()
+}
+
+/// Parses the rules from a TOML string (using `[[rules]]` tables).
+pub fn parse_toml_rules(toml: &str) -> Result<Vec<ServerSoftwareRule>, String> {
+    let raw: RawRules = toml::from_str(toml).map_err(|e| e.to_string())?;
+    raw.rules.into_iter().map(TryFrom::try_from).collect()
+}
+
+/// Returns the name of the first rule that matches, if any.
+pub fn find_software_name(bukkit_version: &str, bukkit_name: Option<&str>) -> Option<String> {
+    let rules = RULES.read().unwrap().clone();
+    match_rules(&rules, bukkit_version, bukkit_name).map(String::from)
+}
+
+fn match_rules<'a>(
+    rules: &'a [ServerSoftwareRule],
+    bukkit_version: &str,
+    bukkit_name: Option<&str>,
+) -> Option<&'a str> {
+    rules
+        .iter()
+        .find(|rule| rule.matches(bukkit_version, bukkit_name))
+        .map(|rule| rule.name.as_str())
+}
+
+/// Replaces the current rules.
+pub fn set_rules(rules: Vec<ServerSoftwareRule>) {
+    *RULES.write().unwrap() = Arc::new(rules);
+}
+
+/// Checks that the last attempt to load the rules from the configured source succeeded.
+pub fn check_rules_loaded() -> Result<(), String> {
+    match LOAD_ERROR.read().unwrap().as_ref() {
+        Some(e) => Err(format!("failed to load server software rules: {}", e)),
+        None => Ok(()),
+    }
+}
+
+/// Where the rules are loaded from.
+#[derive(Debug, Clone)]
+pub enum RulesSource {
+    /// A `.json` or `.toml` file.
+    File(PathBuf),
+    /// A Redis key with a JSON string value.
+    Redis(String),
+}
+
+impl RulesSource {
+    /// Uses the path or, if not set, the Redis key of the config.
+    pub fn from_config(config: &ServerSoftwareRulesConfig) -> Option<Self> {
+        if let Some(path) = &config.path {
+            return Some(RulesSource::File(path.clone()));
+        }
+        config.redis_key.clone().map(RulesSource::Redis)
+    }
+
+    pub async fn load(&self, redis_pool: &RedisPool) -> Result<Vec<ServerSoftwareRule>, String> {
+        match self {
+            RulesSource::File(path) => load_file(path),
+            RulesSource::Redis(key) => {
+                let mut con = redis_pool.get().await.map_err(|e| e.to_string())?;
+                let json: Option<String> = con.get(key).await.map_err(|e| e.to_string())?;
+                match json {
+                    Some(json) => parse_json_rules(&json),
+                    None => Ok(Vec::new()),
+                }
+            }
+        }
+    }
+}
+
+fn load_file(path: &Path) -> Result<Vec<ServerSoftwareRule>, String> {
+    let content = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("toml") => parse_toml_rules(&content),
+        _ => parse_json_rules(&content),
+    }
+}
+
+/// Loads the rules from the configured [`RulesSource`] and reloads them periodically.
+///
+/// If loading fails, the previously loaded rules are kept and the instance is reported
+/// as not ready until the rules could be loaded again. Without a configured source,
+/// only the compiled-in brand table is used.
+pub fn spawn_rules_reloader(redis_pool: RedisPool, config: &ServerSoftwareRulesConfig) {
+    let Some(source) = RulesSource::from_config(config) else {
+        return;
+    };
+    let reload_interval = config.reload_interval;
+
+    actix_web::rt::spawn(async move {
+        let mut interval = actix_web::rt::time::interval(Duration::from_secs(reload_interval));
+        loop {
+            interval.tick().await;
+            match source.load(&redis_pool).await {
+                Ok(rules) => {
+                    tracing::debug!(rules = rules.len(), "Loaded server software rules");
+                    set_rules(rules);
+                    *LOAD_ERROR.write().unwrap() = None;
+                }
+                Err(e) => {
+                    tracing::warn!(
+                        ?source,
+                        error = %e,
+                        "Failed to load server software rules"
+                    );
+                    *LOAD_ERROR.write().unwrap() = Some(e);
+                }
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_rules() {
+        let json = r#"{
+            "rules": [
+                { "name": "Foo", "field": "bukkitVersion", "contains": "FOO" },
+                { "name": "Bar", "field": "bukkitName", "equals": "bar" },
+                { "name": "Baz", "field": "bukkitVersion", "regex": "^baz-\\d+" }
+            ]
+        }"#;
+        let toml = r#"
+            [[rules]]
+            name = "Foo"
+            field = "bukkitVersion"
+            contains = "FOO"
+
+            [[rules]]
+            name = "Bar"
+            field = "bukkitName"
+            equals = "bar"
+
+            [[rules]]
+            name = "Baz"
+            field = "bukkitVersion"
+            regex = '^baz-\d+'
+        "#;
+
+        for rules in [parse_json_rules(json), parse_toml_rules(toml)] {
+            let rules = rules.unwrap();
+            assert_eq!(
+                match_rules(&rules, "git-Foo-1 (MC: 1.21)", Some("Paper")),
+                Some("Foo")
+            );
+            assert_eq!(
+                match_rules(&rules, "1.21-1 (MC: 1.21)", Some("BAR")),
+                Some("Bar")
+            );
+            assert_eq!(match_rules(&rules, "1.21-1 (MC: 1.21)", None), None);
+            assert_eq!(
+                match_rules(&rules, "baz-12 (MC: 1.21)", Some("Paper")),
+                Some("Baz")
+            );
+            assert_eq!(match_rules(&rules, "baz (MC: 1.21)", Some("Paper")), None);
+        }
+    }
+
+    #[test]
+    fn test_parse_invalid_rules() {
+        let invalid_rules = [
+            r#"{ "rules": [{ "name": "Foo", "field": "bukkitVersion" }] }"#,
+            r#"{ "rules": [{ "name": "Foo", "field": "bukkitVersion", "contains": "a", "equals": "a" }] }"#,
+            r#"{ "rules": [{ "name": "Foo", "field": "bukkitVersion", "regex": "(" }] }"#,
+            r#"{ "rules": [{ "name": "Foo", "field": "osName", "contains": "a" }] }"#,
+        ];
+
+        for json in invalid_rules {
+            assert!(
+                parse_json_rules(json).is_err(),
+                "Expected error for {}",
+                json
+            );
+        }
+    }
+}
@@ -0,0 +1,118 @@
+use std::collections::HashMap;
+
+use serde_json::{json, Value};
+
+use crate::charts::drilldown_pie::DrilldownPie;
+
+use super::{ParseContext, Parser};
+
+pub struct SpongeVersionParser;
+
+impl Parser for SpongeVersionParser {
+    fn parse(&self, context: &ParseContext) -> Option<Value> {
+        let get = |name: &str| context.schema.extra.get(name).and_then(|v| v.as_str());
+        let (api_version, implementation) = parse_sponge_version(
+            get("spongeApiVersion"),
+            get("spongeImplementationName"),
+            get("spongeImplementationVersion")?,
+        );
+        Some(json!(DrilldownPie {
+            values: HashMap::from([(api_version, HashMap::from([(implementation, 1)]))])
+        }))
+    }
+}
+
+/// Returns the API version (e.g. "API 8.1.0") and the implementation
+/// (e.g. "SpongeVanilla 1.16.5-8.1.0-RC1184").
+fn parse_sponge_version(
+    api_version: Option<&str>,
+    implementation_name: Option<&str>,
+    implementation_version: &str,
+) -> (String, String) {
+    // Implementation versions have the format "$MINECRAFT-$API-RC$BUILD" (SpongeVanilla) or
+    // "$MINECRAFT-$FORGE_BUILD-$API" (SpongeForge), so the API version can be taken from
+    // there if it was not sent
+    let api_version = api_version
+        .or_else(|| {
+            implementation_version
+                .split('-')
+                .skip(1)
+                .find(|part| part.contains('.'))
+        })
+        .unwrap_or("Unknown");
+    let implementation = match implementation_name {
+        Some(name) => format!("{} {}", name, implementation_version),
+        None => implementation_version.to_string(),
+    };
+    (format!("API {}", api_version), implementation)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_parse_sponge_version() {
+        struct TestCase {
+            name: String,
+            api_version: Option<String>,
+            implementation_name: Option<String>,
+            implementation_version: String,
+            expected: (String, String),
+        }
+
+        let test_cases = vec![
+            TestCase {
+                name: String::from("SpongeVanilla 1.16.5"),
+                api_version: Some(String::from("8.1.0")),
+                implementation_name: Some(String::from("SpongeVanilla")),
+                implementation_version: String::from("1.16.5-8.1.0-RC1184"),
+                expected: (
+                    String::from("API 8.1.0"),
+                    String::from("SpongeVanilla 1.16.5-8.1.0-RC1184"),
+                ),
+            },
+            TestCase {
+                name: String::from("SpongeForge 1.12.2 without API version"),
+                api_version: None,
+                implementation_name: Some(String::from("SpongeForge")),
+                implementation_version: String::from("1.12.2-2838-7.4.7"),
+                expected: (
+                    String::from("API 7.4.7"),
+                    String::from("SpongeForge 1.12.2-2838-7.4.7"),
+                ),
+            },
+            TestCase {
+                name: String::from("SpongeVanilla 1.21 without name"),
+                api_version: Some(String::from("12.0.0")),
+                implementation_name: None,
+                implementation_version: String::from("1.21-12.0.0-RC1822"),
+                expected: (
+                    String::from("API 12.0.0"),
+                    String::from("1.21-12.0.0-RC1822"),
+                ),
+            },
+            TestCase {
+                name: String::from("Unknown format"),
+                api_version: None,
+                implementation_name: None,
+                implementation_version: String::from("custom"),
+                expected: (String::from("API Unknown"), String::from("custom")),
+            },
+        ];
+
+        for test_case in test_cases {
+            let result = parse_sponge_version(
+                test_case.api_version.as_deref(),
+                test_case.implementation_name.as_deref(),
+                &test_case.implementation_version,
+            );
+            assert_eq!(
+                result, test_case.expected,
+                "Wrong result for {}",
+                test_case.name
+            );
+        }
+    }
+}
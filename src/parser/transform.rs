@@ -0,0 +1,268 @@
+use std::collections::HashMap;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use serde_with::skip_serializing_none;
+
+use super::numeric_bucket::Buckets;
+
+/// A transformation of a value from the request, configured with the `transforms` array
+/// of a `nameInRequest` parser, e.g.
+/// `{ "nameInRequest": "pluginVersion", "position": "plugin", "transforms": [{ "type": "trim" }] }`.
+///
+/// If the value is an object with a `value` property (like [`SimplePie`]), the `value`
+/// property is transformed. Transforms that expect a string also accept numbers and booleans.
+///
+/// [`SimplePie`]: crate::charts::simple_pie::SimplePie
+#[skip_serializing_none]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase", deny_unknown_fields)]
+pub enum Transform {
+    /// Removes leading and trailing whitespace.
+    Trim,
+    /// Converts the value to lowercase.
+    Lowercase,
+    /// Replaces the value with a capture group of the regex. Drops the value if the regex
+    /// does not match.
+    Regex {
+        pattern: TransformRegex,
+        /// The capture group, `1` by default.
+        group: Option<usize>,
+    },
+    /// Replaces values that are in `values`. Other values are replaced with `default` or
+    /// kept if there is no default.
+    Map {
+        values: HashMap<String, Value>,
+        default: Option<Value>,
+    },
+    /// Shortens the value to at most `max_length` characters.
+    #[serde(rename_all = "camelCase")]
+    Truncate { max_length: usize },
+    /// Puts a numeric value into ranges, e.g. `{ "type": "bucket", "buckets": [10, 50] }`.
+    Bucket(Buckets),
+}
+
+/// A compiled regex of a [`Transform::Regex`].
+#[derive(Debug, Clone)]
+pub struct TransformRegex(Regex);
+
+impl PartialEq for TransformRegex {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.as_str() == other.0.as_str()
+    }
+}
+
+impl Serialize for TransformRegex {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.0.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for TransformRegex {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let pattern = String::deserialize(deserializer)?;
+        Regex::new(&pattern)
+            .map(TransformRegex)
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+impl Transform {
+    /// Applies the transform. Returns `None` if the value should be dropped.
+    pub fn apply(&self, value: Value) -> Option<Value> {
+        if let Value::Object(mut object) = value {
+            let inner = object.remove("value")?;
+            object.insert(String::from("value"), self.apply(inner)?);
+            return Some(Value::Object(object));
+        }
+
+        match self {
+            Transform::Trim => Some(Value::String(as_string(&value)?.trim().to_string())),
+            Transform::Lowercase => Some(Value::String(as_string(&value)?.to_lowercase())),
+            Transform::Regex { pattern, group } => {
+                let value = as_string(&value)?;
+                let captures = pattern.0.captures(&value)?;
+                let capture = captures.get(group.unwrap_or(1))?;
+                Some(Value::String(capture.as_str().to_string()))
+            }
+            Transform::Map { values, default } => {
+                let key = as_string(&value)?;
+                match values.get(&key).or(default.as_ref()) {
+                    Some(mapped) => Some(mapped.clone()),
+                    None => Some(value),
+                }
+            }
+            Transform::Truncate { max_length } => {
+                let value = as_string(&value)?;
+                Some(Value::String(value.chars().take(*max_length).collect()))
+            }
+            Transform::Bucket(buckets) => {
+                let number = match &value {
+                    Value::Number(n) => n.as_f64()?,
+                    Value::String(s) => s.trim().parse().ok()?,
+                    _ => return None,
+                };
+                let label = buckets.get(number)?;
+                Some(Value::String(label.to_string()))
+            }
+        }
+    }
+}
+
+/// Applies all transforms in order.
+pub fn apply_all(transforms: &[Transform], value: Value) -> Option<Value> {
+    transforms
+        .iter()
+        .try_fold(value, |value, transform| transform.apply(value))
+}
+
+fn as_string(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_apply_transforms() {
+        struct TestCase {
+            name: String,
+            transforms: Value,
+            value: Value,
+            expected: Option<Value>,
+        }
+
+        let test_cases = vec![
+            TestCase {
+                name: String::from("Trim and lowercase"),
+                transforms: json!([{ "type": "trim" }, { "type": "lowercase" }]),
+                value: json!("  Online "),
+                expected: Some(json!("online")),
+            },
+            TestCase {
+                name: String::from("Regex capture"),
+                transforms: json!([{ "type": "regex", "pattern": "^v?(\\d+\\.\\d+)" }]),
+                value: json!("v2.4.1-SNAPSHOT"),
+                expected: Some(json!("2.4")),
+            },
+            TestCase {
+                name: String::from("Regex capture with group 0"),
+                transforms: json!([{ "type": "regex", "pattern": "\\d+", "group": 0 }]),
+                value: json!("build 123"),
+                expected: Some(json!("123")),
+            },
+            TestCase {
+                name: String::from("Regex without match"),
+                transforms: json!([{ "type": "regex", "pattern": "^(\\d+)" }]),
+                value: json!("unknown"),
+                expected: None,
+            },
+            TestCase {
+                name: String::from("Map alias"),
+                transforms: json!([{ "type": "map", "values": { "sqlite": "SQLite" } }]),
+                value: json!("sqlite"),
+                expected: Some(json!("SQLite")),
+            },
+            TestCase {
+                name: String::from("Map keeps unknown values"),
+                transforms: json!([{ "type": "map", "values": { "sqlite": "SQLite" } }]),
+                value: json!("MySQL"),
+                expected: Some(json!("MySQL")),
+            },
+            TestCase {
+                name: String::from("Map with default"),
+                transforms: json!([{
+                    "type": "map",
+                    "values": { "true": "online" },
+                    "default": "offline"
+                }]),
+                value: json!(false),
+                expected: Some(json!("offline")),
+            },
+            TestCase {
+                name: String::from("Truncate"),
+                transforms: json!([{ "type": "truncate", "maxLength": 5 }]),
+                value: json!("Ünïcödé"),
+                expected: Some(json!("Ünïcö")),
+            },
+            TestCase {
+                name: String::from("Bucket"),
+                transforms: json!([{ "type": "bucket", "buckets": [10, 50] }]),
+                value: json!(42),
+                expected: Some(json!("11-50")),
+            },
+            TestCase {
+                name: String::from("Bucket with non-numeric value"),
+                transforms: json!([{ "type": "bucket", "buckets": [10, 50] }]),
+                value: json!("many"),
+                expected: None,
+            },
+            TestCase {
+                name: String::from("Bucket with labels"),
+                transforms: json!([{
+                    "type": "bucket",
+                    "buckets": [0],
+                    "labels": ["None", "Some"]
+                }]),
+                value: json!("3"),
+                expected: Some(json!("Some")),
+            },
+            TestCase {
+                name: String::from("Object with value"),
+                transforms: json!([{ "type": "lowercase" }]),
+                value: json!({ "value": "SQLite" }),
+                expected: Some(json!({ "value": "sqlite" })),
+            },
+            TestCase {
+                name: String::from("Array"),
+                transforms: json!([{ "type": "lowercase" }]),
+                value: json!(["SQLite"]),
+                expected: None,
+            },
+            TestCase {
+                name: String::from("No transforms"),
+                transforms: json!([]),
+                value: json!(["SQLite"]),
+                expected: Some(json!(["SQLite"])),
+            },
+        ];
+
+        for test_case in test_cases {
+            let transforms: Vec<Transform> = serde_json::from_value(test_case.transforms).unwrap();
+            assert_eq!(
+                apply_all(&transforms, test_case.value),
+                test_case.expected,
+                "Wrong result for {}",
+                test_case.name
+            );
+        }
+    }
+
+    #[test]
+    fn test_invalid_transforms() {
+        let invalid_transforms = vec![
+            json!({ "type": "uppercase" }),
+            json!({ "type": "regex", "pattern": "(" }),
+            json!({ "type": "truncate" }),
+            json!({ "type": "bucket", "buckets": [50, 10] }),
+            json!({ "type": "bucket", "buckets": [10], "labels": ["a"] }),
+        ];
+
+        for transform in invalid_transforms {
+            assert!(
+                serde_json::from_value::<Transform>(transform.clone()).is_err(),
+                "Expected error for {}",
+                transform
+            );
+        }
+    }
+}
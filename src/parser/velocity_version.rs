@@ -0,0 +1,84 @@
+use std::collections::HashMap;
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde_json::{json, Value};
+
+use crate::charts::drilldown_pie::DrilldownPie;
+
+use super::{ParseContext, Parser};
+
+pub struct VelocityVersionParser;
+
+impl Parser for VelocityVersionParser {
+    fn parse(&self, context: &ParseContext) -> Option<Value> {
+        let velocity_version = context
+            .schema
+            .extra
+            .get("velocityVersionVersion")
+            .and_then(|v| v.as_str())?;
+        let (version, build) = parse_velocity_version(velocity_version);
+        Some(json!(DrilldownPie {
+            values: HashMap::from([(version, HashMap::from([(build, 1)]))])
+        }))
+    }
+}
+
+static RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^(\S+) \(git-([0-9a-f]+)(?:-b(\d+))?\)$").unwrap());
+
+/// Splits a Velocity version like "3.3.0-SNAPSHOT (git-8cfaa7f0-b436)" into the version
+/// and the build. Releases have no build number, so the commit is used instead.
+fn parse_velocity_version(velocity_version: &str) -> (String, String) {
+    let Some(captures) = RE.captures(velocity_version.trim()) else {
+        return (velocity_version.to_string(), velocity_version.to_string());
+    };
+    let version = captures[1].to_string();
+    let build = match captures.get(3) {
+        Some(build) => format!("Build {}", build.as_str()),
+        None => format!("git-{}", &captures[2]),
+    };
+    (version, build)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_parse_velocity_version() {
+        struct TestCase {
+            name: String,
+            velocity_version: String,
+            expected: (String, String),
+        }
+
+        let test_cases = vec![
+            TestCase {
+                name: String::from("Snapshot with build"),
+                velocity_version: String::from("3.3.0-SNAPSHOT (git-8cfaa7f0-b436)"),
+                expected: (String::from("3.3.0-SNAPSHOT"), String::from("Build 436")),
+            },
+            TestCase {
+                name: String::from("Release without build"),
+                velocity_version: String::from("3.1.1 (git-a4b14a6c)"),
+                expected: (String::from("3.1.1"), String::from("git-a4b14a6c")),
+            },
+            TestCase {
+                name: String::from("Unknown format"),
+                velocity_version: String::from("3.4.0-custom"),
+                expected: (String::from("3.4.0-custom"), String::from("3.4.0-custom")),
+            },
+        ];
+
+        for test_case in test_cases {
+            let result = parse_velocity_version(&test_case.velocity_version);
+            assert_eq!(
+                result, test_case.expected,
+                "Wrong result for {}",
+                test_case.name
+            );
+        }
+    }
+}
@@ -1,5 +1,8 @@
 use redis::AsyncCommands;
 
+use crate::metrics;
+
+#[tracing::instrument(level = "debug", skip(con, server_uuid, ip))]
 pub async fn is_ratelimited<C: AsyncCommands>(
     con: &mut C,
     software_url: &str,
@@ -18,6 +21,8 @@ pub async fn is_ratelimited<C: AsyncCommands>(
     )
     .await?
     {
+        tracing::debug!("Server exceeded the rate limit");
+        metrics::RATELIMIT_HITS.with_label_values(&["server"]).inc();
         return Ok(true);
     }
     if _is_ratelimited(
@@ -29,6 +34,8 @@ pub async fn is_ratelimited<C: AsyncCommands>(
     )
     .await?
     {
+        tracing::debug!("IP exceeded the rate limit");
+        metrics::RATELIMIT_HITS.with_label_values(&["ip"]).inc();
         return Ok(true);
     }
     return Ok(false);
@@ -55,6 +55,15 @@ pub async fn find_by_id<C: AsyncCommands>(
         return Ok(None);
     }
 
+    let default_charts =
+        serde_json::from_str(software.get("defaultCharts").unwrap()).map_err(|e| {
+            redis::RedisError::from((
+                redis::ErrorKind::TypeError,
+                "Software with invalid 'defaultCharts'",
+                format!("software {}: {}", id, e),
+            ))
+        })?;
+
     Ok(Some(Software {
         id,
         name: software.get("name").unwrap().to_string(),
@@ -67,7 +76,7 @@ pub async fn find_by_id<C: AsyncCommands>(
             .get("hideInPluginList")
             .unwrap_or(&String::from("0"))
             != "0",
-        default_charts: serde_json::from_str(software.get("defaultCharts").unwrap()).unwrap(),
+        default_charts,
     }))
 }
 
@@ -0,0 +1,110 @@
+use std::collections::HashMap;
+
+use actix_web::{web, HttpRequest};
+use serde::Serialize;
+use serde_with::skip_serializing_none;
+
+/// A report about how the charts of a submission were processed.
+///
+/// Only returned to the client in debug mode (see [`is_debug_mode`]) to help
+/// plugin authors find out why a chart stays empty.
+#[derive(Debug, Default, Serialize)]
+pub struct SubmissionReport {
+    pub charts: Vec<ChartReport>,
+}
+
+#[skip_serializing_none]
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChartReport {
+    pub chart_id: String,
+    pub status: ChartStatus,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ChartStatus {
+    /// The data was accepted and stored.
+    Accepted,
+    /// The service has no chart with the given id.
+    Unknown,
+    /// The chart exists, but its config is invalid.
+    InvalidConfig,
+    /// The data was blocked by the filter of the chart.
+    Filtered,
+    /// The chart is a default chart, but was sent as custom chart.
+    SpoofedDefaultChart,
+    /// The data does not match the type of the chart.
+    ParseError,
+    /// The parser of a default chart returned no value, e.g. because the property is
+    /// not part of the request.
+    NoValue,
+}
+
+impl SubmissionReport {
+    pub fn add(&mut self, chart_id: &str, status: ChartStatus) {
+        self.charts.push(ChartReport {
+            chart_id: chart_id.to_string(),
+            status,
+            error: None,
+        });
+    }
+
+    pub fn add_error(&mut self, chart_id: &str, status: ChartStatus, error: String) {
+        self.charts.push(ChartReport {
+            chart_id: chart_id.to_string(),
+            status,
+            error: Some(error),
+        });
+    }
+}
+
+/// Checks if the client requested a submission report.
+///
+/// Debug mode is enabled with the `X-Bstats-Debug: true` header or the
+/// `debug=true` query parameter.
+pub fn is_debug_mode(request: &HttpRequest) -> bool {
+    let header = request
+        .headers()
+        .get("x-bstats-debug")
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.eq_ignore_ascii_case("true"))
+        .unwrap_or(false);
+    if header {
+        return true;
+    }
+
+    web::Query::<HashMap<String, String>>::from_query(request.query_string())
+        .map(|query| query.get("debug").map(|v| v == "true").unwrap_or(false))
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::test;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_is_debug_mode() {
+        let req = test::TestRequest::post().uri("/bukkit").to_http_request();
+        assert!(!is_debug_mode(&req));
+
+        let req = test::TestRequest::post()
+            .uri("/bukkit?debug=true")
+            .to_http_request();
+        assert!(is_debug_mode(&req));
+
+        let req = test::TestRequest::post()
+            .uri("/bukkit?debug=false")
+            .to_http_request();
+        assert!(!is_debug_mode(&req));
+
+        let req = test::TestRequest::post()
+            .uri("/bukkit")
+            .insert_header(("X-Bstats-Debug", "true"))
+            .to_http_request();
+        assert!(is_debug_mode(&req));
+    }
+}
@@ -1,3 +1,4 @@
 pub mod geo_ip;
 pub mod ip_parser;
+pub mod logging;
 pub mod redis;
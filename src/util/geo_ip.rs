@@ -1,23 +1,43 @@
-use std::net::IpAddr;
+use std::{net::IpAddr, path::Path};
 
 use maxminddb::{geoip2, MaxMindDBError, Reader};
-use once_cell::sync::Lazy;
+use once_cell::sync::OnceCell;
 use phf::phf_map;
 
-static READER: Lazy<Result<Reader<Vec<u8>>, MaxMindDBError>> = Lazy::new(|| {
-    let file_path =
-        std::env::var("GEOIP_DATABASE_PATH").unwrap_or(String::from("GeoLite2-Country.mmdb"));
-    let reader = maxminddb::Reader::open_readfile(file_path);
+use crate::metrics;
+
+/// The path of the GeoIP database if no other path is configured.
+pub const DEFAULT_DATABASE_PATH: &str = "GeoLite2-Country.mmdb";
+
+static READER: OnceCell<Result<Reader<Vec<u8>>, MaxMindDBError>> = OnceCell::new();
+
+/// Opens the GeoIP database at the given path.
+///
+/// This must be called before the first lookup, otherwise the database at
+/// [`DEFAULT_DATABASE_PATH`] is used.
+pub fn load_database(path: &Path) {
+    if READER.set(open_database(path)).is_err() {
+        tracing::warn!("GeoIP database was already loaded");
+    }
+}
+
+fn open_database(path: &Path) -> Result<Reader<Vec<u8>>, MaxMindDBError> {
+    let reader = maxminddb::Reader::open_readfile(path);
     if reader.is_err() {
-        eprintln!(
-            "Failed to open GeoLite2-Country.mmdb: {:?}",
-            reader.as_ref().err()
+        tracing::error!(
+            path = %path.display(),
+            error = ?reader.as_ref().err(),
+            "Failed to open GeoIP database"
         );
     }
     reader
-});
+}
+
+fn reader() -> &'static Result<Reader<Vec<u8>>, MaxMindDBError> {
+    READER.get_or_init(|| open_database(Path::new(DEFAULT_DATABASE_PATH)))
+}
 
-static ISO_COUNTRIES: phf::Map<&'static str, &'static str> = phf_map! {
+pub static ISO_COUNTRIES: phf::Map<&'static str, &'static str> = phf_map! {
     "AF" => "Afghanistan",
     "AX" => "Åland Islands",
     "AL" => "Albania",
@@ -261,23 +281,40 @@ static ISO_COUNTRIES: phf::Map<&'static str, &'static str> = phf_map! {
 type CountryName = String;
 type IsoCode = String;
 
+/// Returns whether the GeoIP database was opened successfully.
+pub fn is_loaded() -> bool {
+    reader().is_ok()
+}
+
 /// Get the country code and name for an IP address.
 pub fn get_country(ip: IpAddr) -> Option<(IsoCode, Option<CountryName>)> {
-    if let Ok(reader) = READER.as_ref() {
+    if let Ok(reader) = reader() {
         let country: geoip2::Country = match reader.lookup(ip) {
             Ok(c) => c,
             Err(e) => {
-                eprintln!("Failed to lookup country for IP {:?}: {:?}", ip, e);
+                tracing::debug!(%ip, error = ?e, "Failed to lookup country");
+                let result = match e {
+                    MaxMindDBError::AddressNotFoundError(_) => "not_found",
+                    _ => "error",
+                };
+                metrics::GEOIP_LOOKUPS.with_label_values(&[result]).inc();
                 return None;
             }
         };
-        let iso_code = country.country?.iso_code?;
+        let Some(iso_code) = country.country.and_then(|c| c.iso_code) else {
+            metrics::GEOIP_LOOKUPS
+                .with_label_values(&["not_found"])
+                .inc();
+            return None;
+        };
+        metrics::GEOIP_LOOKUPS.with_label_values(&["found"]).inc();
 
         return Some((
             iso_code.to_string(),
             ISO_COUNTRIES.get(iso_code).map(|s| s.to_string()),
         ));
     }
+    metrics::GEOIP_LOOKUPS.with_label_values(&["error"]).inc();
     return None;
 }
 
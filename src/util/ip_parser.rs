@@ -1,19 +1,17 @@
 use actix_web::{error, HttpRequest};
 
-/// Get the IP address of the client making the request.
+use crate::config::Config;
 
-pub fn get_ip(request: &HttpRequest) -> Result<String, error::Error> {
-    let behind_cloudflare =
-        std::env::var("BEHIND_CLOUDFLARE_PROXY").unwrap_or(String::from("false")) == "true";
-    let behind_proxy = std::env::var("BEHIND_PROXY").unwrap_or(String::from("false")) == "true";
+/// Get the IP address of the client making the request.
 
-    if behind_cloudflare {
+pub fn get_ip(request: &HttpRequest, config: &Config) -> Result<String, error::Error> {
+    if config.behind_cloudflare_proxy {
         if let Some(header) = request.headers().get("cf-connecting-ip") {
             return Ok(header.to_str().unwrap().to_string());
         }
     }
 
-    if behind_proxy {
+    if config.behind_proxy {
         return Ok(request
             .connection_info()
             .realip_remote_addr()
@@ -49,20 +47,22 @@ mod tests {
             ))
             .to_http_request();
 
+        let mut config = Config::default();
+
         // Should not use proxy ips when not behind Cloudflare
-        let ip = get_ip(&req).unwrap();
+        let ip = get_ip(&req, &config).unwrap();
         assert_eq!(ip, "1.1.1.1");
 
         // Should use Cloudflare header when behind Cloudflare
-        std::env::set_var("BEHIND_CLOUDFLARE_PROXY", "true");
-        let ip = get_ip(&req).unwrap();
+        config.behind_cloudflare_proxy = true;
+        let ip = get_ip(&req, &config).unwrap();
         assert_eq!(ip, "2.2.2.2");
 
         // Should use proxy ip when behind proxy
-        std::env::set_var("BEHIND_PROXY", "true");
-        std::env::set_var("BEHIND_CLOUDFLARE_PROXY", "false");
+        config.behind_proxy = true;
+        config.behind_cloudflare_proxy = false;
 
-        let ip = get_ip(&req).unwrap();
+        let ip = get_ip(&req, &config).unwrap();
         assert_eq!(ip, "4.4.4.4");
     }
 }
@@ -0,0 +1,45 @@
+use sha2::{Digest, Sha256};
+use tracing_subscriber::EnvFilter;
+
+/// Initializes the global logger.
+///
+/// The log level is configured with `RUST_LOG` (e.g. `info` or `data_processor=debug`,
+/// `info` by default). With `LOG_FORMAT=json`, every log line is a JSON object.
+pub fn init_logging() {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    let json = std::env::var("LOG_FORMAT").is_ok_and(|format| format == "json");
+
+    let builder = tracing_subscriber::fmt().with_env_filter(filter);
+    // Fails if a logger has already been set, which is fine
+    let _ = if json {
+        builder.json().with_current_span(true).try_init()
+    } else {
+        builder.try_init()
+    };
+}
+
+/// A short hash of the server UUID, so the logs of a server can be correlated without
+/// logging the UUID itself.
+pub fn hash_server_uuid(server_uuid: &str) -> String {
+    let hash = Sha256::digest(server_uuid.as_bytes());
+    hash[..8].iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hash_server_uuid() {
+        let hash = hash_server_uuid("2d1f3b3e-5c51-4f1b-9d8c-6f4a2b7e9a10");
+        assert_eq!(hash.len(), 16);
+        assert_eq!(
+            hash,
+            hash_server_uuid("2d1f3b3e-5c51-4f1b-9d8c-6f4a2b7e9a10")
+        );
+        assert_ne!(
+            hash,
+            hash_server_uuid("00000000-0000-0000-0000-000000000000")
+        );
+    }
+}
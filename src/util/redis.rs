@@ -1,17 +1,149 @@
-use deadpool::managed::Pool;
-use deadpool_redis::cluster::{Config, Connection, Manager, Runtime};
-use std::env;
-
-pub type RedisClusterPool = Pool<Manager, Connection>;
-
-pub async fn get_redis_cluster_pool() -> RedisClusterPool {
-    let redis_urls = env::var("REDIS_CLUSTER__URLS")
-        .expect("REDIS_CLUSTER__URLS is not set")
-        .split(',')
-        .map(String::from)
-        .collect::<Vec<_>>();
-    let cfg = Config::from_urls(redis_urls);
-    let pool = cfg.create_pool(Some(Runtime::Tokio1)).unwrap();
-
-    return pool;
+use deadpool::managed::{self, Metrics, PoolError, RecycleError, RecycleResult, Status};
+use deadpool_redis::{cluster, Runtime};
+use redis::{
+    aio::{ConnectionLike, MultiplexedConnection},
+    sentinel::{SentinelClient, SentinelServerType},
+    Cmd, Pipeline, RedisError, RedisFuture, Value,
+};
+
+use crate::config::{RedisConfig, RedisMode};
+
+/// A pool of connections to a Redis cluster, a single Redis node or the primary of
+/// a Sentinel-managed deployment, depending on [`RedisConfig::mode`].
+#[derive(Clone)]
+pub enum RedisPool {
+    Cluster(cluster::Pool),
+    Standalone(deadpool_redis::Pool),
+    Sentinel(managed::Pool<SentinelManager>),
+}
+
+/// A connection taken from a [`RedisPool`]. It is returned to the pool when dropped.
+pub enum RedisConnection {
+    Cluster(cluster::Connection),
+    Standalone(deadpool_redis::Connection),
+    Sentinel(managed::Object<SentinelManager>),
+}
+
+impl RedisPool {
+    pub async fn get(&self) -> Result<RedisConnection, PoolError<RedisError>> {
+        Ok(match self {
+            RedisPool::Cluster(pool) => RedisConnection::Cluster(pool.get().await?),
+            RedisPool::Standalone(pool) => RedisConnection::Standalone(pool.get().await?),
+            RedisPool::Sentinel(pool) => RedisConnection::Sentinel(pool.get().await?),
+        })
+    }
+
+    pub fn status(&self) -> Status {
+        match self {
+            RedisPool::Cluster(pool) => pool.status(),
+            RedisPool::Standalone(pool) => pool.status(),
+            RedisPool::Sentinel(pool) => pool.status(),
+        }
+    }
+}
+
+impl ConnectionLike for RedisConnection {
+    fn req_packed_command<'a>(&'a mut self, cmd: &'a Cmd) -> RedisFuture<'a, Value> {
+        match self {
+            RedisConnection::Cluster(con) => con.req_packed_command(cmd),
+            RedisConnection::Standalone(con) => con.req_packed_command(cmd),
+            RedisConnection::Sentinel(con) => con.req_packed_command(cmd),
+        }
+    }
+
+    fn req_packed_commands<'a>(
+        &'a mut self,
+        cmd: &'a Pipeline,
+        offset: usize,
+        count: usize,
+    ) -> RedisFuture<'a, Vec<Value>> {
+        match self {
+            RedisConnection::Cluster(con) => con.req_packed_commands(cmd, offset, count),
+            RedisConnection::Standalone(con) => con.req_packed_commands(cmd, offset, count),
+            RedisConnection::Sentinel(con) => con.req_packed_commands(cmd, offset, count),
+        }
+    }
+
+    fn get_db(&self) -> i64 {
+        match self {
+            RedisConnection::Cluster(con) => con.get_db(),
+            RedisConnection::Standalone(con) => con.get_db(),
+            RedisConnection::Sentinel(con) => con.get_db(),
+        }
+    }
+}
+
+/// Creates connections to the current primary of a Sentinel-managed deployment.
+///
+/// The primary is looked up for every new connection, and connections to a node
+/// that is no longer the primary (e.g. after a failover) are not reused.
+pub struct SentinelManager {
+    sentinel_urls: Vec<String>,
+    master_name: String,
+}
+
+impl managed::Manager for SentinelManager {
+    type Type = MultiplexedConnection;
+    type Error = RedisError;
+
+    async fn create(&self) -> Result<MultiplexedConnection, RedisError> {
+        let mut client = SentinelClient::build(
+            self.sentinel_urls.clone(),
+            self.master_name.clone(),
+            None,
+            SentinelServerType::Master,
+        )?;
+        client.get_async_connection().await
+    }
+
+    async fn recycle(
+        &self,
+        con: &mut MultiplexedConnection,
+        _: &Metrics,
+    ) -> RecycleResult<RedisError> {
+        let role: Vec<Value> = redis::cmd("ROLE").query_async(con).await?;
+        let role: Option<String> = role
+            .first()
+            .and_then(|role| redis::from_redis_value(role).ok());
+        if role.as_deref() == Some("master") {
+            Ok(())
+        } else {
+            Err(RecycleError::message("Connection is not to the primary"))
+        }
+    }
+}
+
+/// Creates the pool for the given config, which must have been validated before.
+pub async fn get_redis_pool(config: &RedisConfig) -> Result<RedisPool, String> {
+    match config.mode {
+        RedisMode::Cluster => {
+            let cfg = cluster::Config::from_urls(config.cluster_urls.clone());
+            cfg.create_pool(Some(Runtime::Tokio1))
+                .map(RedisPool::Cluster)
+                .map_err(|e| e.to_string())
+        }
+        RedisMode::Standalone => {
+            let url = config
+                .url
+                .clone()
+                .ok_or_else(|| String::from("REDIS_URL is not set"))?;
+            let cfg = deadpool_redis::Config::from_url(url);
+            cfg.create_pool(Some(Runtime::Tokio1))
+                .map(RedisPool::Standalone)
+                .map_err(|e| e.to_string())
+        }
+        RedisMode::Sentinel => {
+            let manager = SentinelManager {
+                sentinel_urls: config.sentinel_urls.clone(),
+                master_name: config
+                    .sentinel_master_name
+                    .clone()
+                    .ok_or_else(|| String::from("REDIS_SENTINEL__MASTER_NAME is not set"))?,
+            };
+            managed::Pool::builder(manager)
+                .build()
+                .map(RedisPool::Sentinel)
+                .map_err(|e| e.to_string())
+        }
+    }
 }
@@ -1,4 +1,7 @@
-use data_processor::util::redis::{get_redis_cluster_pool, RedisClusterPool};
+use data_processor::{
+    config::RedisConfig,
+    util::redis::{get_redis_pool, RedisPool},
+};
 use testcontainers::{
     core::{ExecCommand, IntoContainerPort, WaitFor},
     runners::AsyncRunner,
@@ -6,7 +9,8 @@ use testcontainers::{
 };
 
 pub struct RedisTestcontainer {
-    pool: RedisClusterPool,
+    pool: RedisPool,
+    redis_config: RedisConfig,
     // Bind the container to the struct to keep it alive
     _container: ContainerAsync<GenericImage>,
 }
@@ -92,19 +96,28 @@ impl RedisTestcontainer {
                 .expect("Failed to set cluster-announce-port");
         }
 
-        std::env::set_var("REDIS_CLUSTER__URLS", &redis_addr);
-
         println!("Redis container started at {}", &redis_addr);
 
-        let pool = get_redis_cluster_pool().await;
+        let redis_config = RedisConfig {
+            cluster_urls: vec![redis_addr],
+            ..RedisConfig::default()
+        };
+        let pool = get_redis_pool(&redis_config)
+            .await
+            .expect("Failed to create Redis pool");
 
         Self {
             pool,
+            redis_config,
             _container: container,
         }
     }
 
-    pub fn pool(&self) -> &RedisClusterPool {
+    pub fn pool(&self) -> &RedisPool {
         &self.pool
     }
+
+    pub fn redis_config(&self) -> &RedisConfig {
+        &self.redis_config
+    }
 }
@@ -1,13 +1,15 @@
+use data_processor::util::redis::RedisConnection;
 use data_processor::{
     charts::{
         chart::{ChartType, DefaultChartTemplate},
+        chart_config::ChartConfig,
         Chart,
     },
+    config::Config,
     service::Service,
     software::Software,
-    util::redis::RedisClusterPool,
+    util::redis::RedisPool,
 };
-use deadpool_redis::cluster::Connection;
 use redis::AsyncCommands;
 use serde_json::json;
 
@@ -188,10 +190,17 @@ impl TestEnvironment {
         self.charts.push(cloned_chart);
     }
 
-    pub fn redis_pool(&self) -> &RedisClusterPool {
+    pub fn redis_pool(&self) -> &RedisPool {
         &self.redis_testcontainer.pool()
     }
 
+    pub fn config(&self) -> Config {
+        Config {
+            redis: self.redis_testcontainer.redis_config().clone(),
+            ..Config::default()
+        }
+    }
+
     pub fn software(&self) -> &Vec<Software> {
         &self.software
     }
@@ -204,7 +213,7 @@ impl TestEnvironment {
         &self.charts
     }
 
-    pub async fn redis_connection(&self) -> Connection {
+    pub async fn redis_connection(&self) -> RedisConnection {
         self.redis_pool().get().await.unwrap()
     }
 }
@@ -220,215 +229,287 @@ pub fn get_bukkit_software() -> Software {
         max_requests_per_ip: 10,
         hide_in_plugin_list: false,
         default_charts: vec![
-            DefaultChartTemplate {
-                id: String::from("servers"),
-                chart_type: ChartType::SingleLineChart,
-                title: String::from("Servers using %plugin.name%"),
-                data: json!({
-                    "lineName": "Servers",
-                    "filter": {
-                        "enabled": false,
-                        "maxValue": 1,
-                        "minValue": 1
-                    }
-                }),
-                request_parser: json!({
+            DefaultChartTemplate::new(
+                String::from("servers"),
+                ChartType::SingleLineChart,
+                String::from("Servers using %plugin.name%"),
+                ChartConfig::from_value(
+                    &ChartType::SingleLineChart,
+                    json!({
+                        "lineName": "Servers",
+                        "filter": {
+                            "enabled": false,
+                            "maxValue": 1,
+                            "minValue": 1
+                        }
+                    }),
+                )
+                .unwrap(),
+                serde_json::from_value(json!({
                     "predefinedValue": 1
-                }),
-            },
-            DefaultChartTemplate {
-                id: String::from("players"),
-                chart_type: ChartType::SingleLineChart,
-                title: String::from("Players on servers using %plugin.name%"),
-                data: json!({
-                    "lineName": "Players",
-                    "filter": {
-                        "enabled": true,
-                        "maxValue": 200,
-                        "minValue": 0
-                    }
-                }),
-                request_parser: json!({
+                }))
+                .unwrap(),
+            )
+            .unwrap(),
+            DefaultChartTemplate::new(
+                String::from("players"),
+                ChartType::SingleLineChart,
+                String::from("Players on servers using %plugin.name%"),
+                ChartConfig::from_value(
+                    &ChartType::SingleLineChart,
+                    json!({
+                        "lineName": "Players",
+                        "filter": {
+                            "enabled": true,
+                            "maxValue": 200,
+                            "minValue": 0
+                        }
+                    }),
+                )
+                .unwrap(),
+                serde_json::from_value(json!({
                     "nameInRequest": "playerAmount",
                     "type": "number",
                     "position": "global"
-                }),
-            },
-            DefaultChartTemplate {
-                id: String::from("onlineMode"),
-                chart_type: ChartType::SimplePie,
-                title: String::from("Online mode"),
-                data: json!({
-                    "filter": {
-                        "enabled": false,
-                        "useRegex": false,
-                        "blacklist": false,
-                        "filter": []
-                    }
-                }),
-                request_parser: json!({
+                }))
+                .unwrap(),
+            )
+            .unwrap(),
+            DefaultChartTemplate::new(
+                String::from("onlineMode"),
+                ChartType::SimplePie,
+                String::from("Online mode"),
+                ChartConfig::from_value(
+                    &ChartType::SimplePie,
+                    json!({
+                        "filter": {
+                            "enabled": false,
+                            "useRegex": false,
+                            "blacklist": false,
+                            "filter": []
+                        }
+                    }),
+                )
+                .unwrap(),
+                serde_json::from_value(json!({
                     "nameInRequest": "onlineMode",
                     "position": "global",
                     "type": "boolean",
                     "trueValue": "online",
                     "falseValue": "offline"
-                }),
-            },
-            DefaultChartTemplate {
-                id: String::from("minecraftVersion"),
-                chart_type: ChartType::SimplePie,
-                title: String::from("Minecraft Version"),
-                data: json!({
-                    "filter": {
-                        "enabled": false,
-                        "useRegex": false,
-                        "blacklist": false,
-                        "filter": []
-                    }
-                }),
-                request_parser: json!({
+                }))
+                .unwrap(),
+            )
+            .unwrap(),
+            DefaultChartTemplate::new(
+                String::from("minecraftVersion"),
+                ChartType::SimplePie,
+                String::from("Minecraft Version"),
+                ChartConfig::from_value(
+                    &ChartType::SimplePie,
+                    json!({
+                        "filter": {
+                            "enabled": false,
+                            "useRegex": false,
+                            "blacklist": false,
+                            "filter": []
+                        }
+                    }),
+                )
+                .unwrap(),
+                serde_json::from_value(json!({
                     "useHardcodedParser": "bukkitMinecraftVersion",
                     "position": "global"
-                }),
-            },
-            DefaultChartTemplate {
-                id: String::from("serverSoftware"),
-                chart_type: ChartType::SimplePie,
-                title: String::from("Server Software"),
-                data: json!({
-                    "filter": {
-                        "enabled": false,
-                        "useRegex": false,
-                        "blacklist": false,
-                        "filter": []
-                    }
-                }),
-                request_parser: json!({
+                }))
+                .unwrap(),
+            )
+            .unwrap(),
+            DefaultChartTemplate::new(
+                String::from("serverSoftware"),
+                ChartType::SimplePie,
+                String::from("Server Software"),
+                ChartConfig::from_value(
+                    &ChartType::SimplePie,
+                    json!({
+                        "filter": {
+                            "enabled": false,
+                            "useRegex": false,
+                            "blacklist": false,
+                            "filter": []
+                        }
+                    }),
+                )
+                .unwrap(),
+                serde_json::from_value(json!({
                     "useHardcodedParser": "bukkitServerSoftware",
                     "position": "global"
-                }),
-            },
-            DefaultChartTemplate {
-                id: String::from("pluginVersion"),
-                chart_type: ChartType::SimplePie,
-                title: String::from("Plugin Version"),
-                data: json!({
-                    "filter": {
-                        "enabled": false,
-                        "useRegex": false,
-                        "blacklist": false,
-                        "filter": []
-                    }
-                }),
-                request_parser: json!({
+                }))
+                .unwrap(),
+            )
+            .unwrap(),
+            DefaultChartTemplate::new(
+                String::from("pluginVersion"),
+                ChartType::SimplePie,
+                String::from("Plugin Version"),
+                ChartConfig::from_value(
+                    &ChartType::SimplePie,
+                    json!({
+                        "filter": {
+                            "enabled": false,
+                            "useRegex": false,
+                            "blacklist": false,
+                            "filter": []
+                        }
+                    }),
+                )
+                .unwrap(),
+                serde_json::from_value(json!({
                     "nameInRequest": "pluginVersion",
                     "position": "plugin"
-                }),
-            },
-            DefaultChartTemplate {
-                id: String::from("coreCount"),
-                chart_type: ChartType::SimplePie,
-                title: String::from("Core count"),
-                data: json!({
-                    "filter": {
-                        "enabled": true,
-                        "useRegex": true,
-                        "blacklist": false,
-                        "filter": [
-                            "([0-9]){1,2}"
-                        ]
-                    }
-                }),
-                request_parser: json!({
+                }))
+                .unwrap(),
+            )
+            .unwrap(),
+            DefaultChartTemplate::new(
+                String::from("coreCount"),
+                ChartType::SimplePie,
+                String::from("Core count"),
+                ChartConfig::from_value(
+                    &ChartType::SimplePie,
+                    json!({
+                        "filter": {
+                            "enabled": true,
+                            "useRegex": true,
+                            "blacklist": false,
+                            "filter": [
+                                "([0-9]){1,2}"
+                            ]
+                        }
+                    }),
+                )
+                .unwrap(),
+                serde_json::from_value(json!({
                     "nameInRequest": "coreCount",
                     "type": "number",
                     "position": "global"
-                }),
-            },
-            DefaultChartTemplate {
-                id: String::from("osArch"),
-                chart_type: ChartType::SimplePie,
-                title: String::from("System arch"),
-                data: json!({
-                    "filter": {
-                        "enabled": false,
-                        "useRegex": false,
-                        "blacklist": false,
-                        "filter": []
-                    }
-                }),
-                request_parser: json!({
+                }))
+                .unwrap(),
+            )
+            .unwrap(),
+            DefaultChartTemplate::new(
+                String::from("osArch"),
+                ChartType::SimplePie,
+                String::from("System arch"),
+                ChartConfig::from_value(
+                    &ChartType::SimplePie,
+                    json!({
+                        "filter": {
+                            "enabled": false,
+                            "useRegex": false,
+                            "blacklist": false,
+                            "filter": []
+                        }
+                    }),
+                )
+                .unwrap(),
+                serde_json::from_value(json!({
                     "nameInRequest": "osArch",
                     "position": "global"
-                }),
-            },
-            DefaultChartTemplate {
-                id: String::from("os"),
-                chart_type: ChartType::DrilldownPie,
-                title: String::from("Operating System"),
-                data: json!({
-                    "filter": {
-                        "enabled": false,
-                        "useRegex": false,
-                        "blacklist": false,
-                        "filter": []
-                    }
-                }),
-                request_parser: json!({
+                }))
+                .unwrap(),
+            )
+            .unwrap(),
+            DefaultChartTemplate::new(
+                String::from("os"),
+                ChartType::DrilldownPie,
+                String::from("Operating System"),
+                ChartConfig::from_value(
+                    &ChartType::DrilldownPie,
+                    json!({
+                        "filter": {
+                            "enabled": false,
+                            "useRegex": false,
+                            "blacklist": false,
+                            "filter": []
+                        }
+                    }),
+                )
+                .unwrap(),
+                serde_json::from_value(json!({
                     "position": "global",
                     "useHardcodedParser": "os"
-                }),
-            },
-            DefaultChartTemplate {
-                id: String::from("location"),
-                chart_type: ChartType::SimplePie,
-                title: String::from("Server Location"),
-                data: json!({
-                    "filter": {
-                        "enabled": false,
-                        "useRegex": false,
-                        "blacklist": false,
-                        "filter": []
-                    }
-                }),
-                request_parser: json!({
+                }))
+                .unwrap(),
+            )
+            .unwrap(),
+            DefaultChartTemplate::new(
+                String::from("location"),
+                ChartType::SimplePie,
+                String::from("Server Location"),
+                ChartConfig::from_value(
+                    &ChartType::SimplePie,
+                    json!({
+                        "filter": {
+                            "enabled": false,
+                            "useRegex": false,
+                            "blacklist": false,
+                            "filter": []
+                        }
+                    }),
+                )
+                .unwrap(),
+                serde_json::from_value(json!({
                     "predefinedValue": "%country.name%"
-                }),
-            },
-            DefaultChartTemplate {
-                id: String::from("javaVersion"),
-                chart_type: ChartType::DrilldownPie,
-                title: String::from("Java Version"),
-                data: json!({
-                    "filter": {
-                        "enabled": false,
-                        "useRegex": false,
-                        "blacklist": false,
-                        "filter": []
-                    }
-                }),
-                request_parser: json!({
+                }))
+                .unwrap(),
+            )
+            .unwrap(),
+            DefaultChartTemplate::new(
+                String::from("javaVersion"),
+                ChartType::DrilldownPie,
+                String::from("Java Version"),
+                ChartConfig::from_value(
+                    &ChartType::DrilldownPie,
+                    json!({
+                        "filter": {
+                            "enabled": false,
+                            "useRegex": false,
+                            "blacklist": false,
+                            "filter": []
+                        }
+                    }),
+                )
+                .unwrap(),
+                serde_json::from_value(json!({
                     "useHardcodedParser": "javaVersion",
                     "position": "global"
-                }),
-            },
-            DefaultChartTemplate {
-                id: String::from("locationMap"),
-                chart_type: ChartType::SimpleMap,
-                title: String::from("Server Location"),
-                data: json!({
-                    "valueName": "Servers",
-                    "filter": {
-                        "enabled": false,
-                        "useRegex": false,
-                        "blacklist": false,
-                        "filter": []
-                    }
-                }),
-                request_parser: json!({
+                }))
+                .unwrap(),
+            )
+            .unwrap(),
+            DefaultChartTemplate::new(
+                String::from("locationMap"),
+                ChartType::SimpleMap,
+                String::from("Server Location"),
+                ChartConfig::from_value(
+                    &ChartType::SimpleMap,
+                    json!({
+                        "valueName": "Servers",
+                        "filter": {
+                            "enabled": false,
+                            "useRegex": false,
+                            "blacklist": false,
+                            "filter": []
+                        }
+                    }),
+                )
+                .unwrap(),
+                serde_json::from_value(json!({
                     "predefinedValue": "AUTO"
-                }),
-            },
+                }))
+                .unwrap(),
+            )
+            .unwrap(),
         ]
     }
 }
@@ -496,14 +577,18 @@ pub fn get_line_chart(service_id: u32, id: u64, id_custom: String) -> Chart {
         position: 0,
         title: String::from("My fancy line chart"),
         default: false,
-        data: json!({
-            "lineName": "My fancy line",
-            "filter": {
-                "enabled": true,
-                "maxValue": 1000,
-                "minValue": 0
-            }
-        }),
+        data: ChartConfig::from_value(
+            &ChartType::SingleLineChart,
+            json!({
+                "lineName": "My fancy line",
+                "filter": {
+                    "enabled": true,
+                    "maxValue": 1000,
+                    "minValue": 0
+                }
+            }),
+        )
+        .unwrap(),
         service_id,
     }
 }
@@ -1,5 +1,6 @@
 pub mod helper;
 pub mod test_charts;
+pub mod test_health;
 pub mod test_ratelimits;
 pub mod test_service;
 pub mod test_software;
@@ -1,5 +1,17 @@
+use std::collections::HashMap;
+
 use crate::helper::test_environment::TestEnvironment;
-use data_processor::charts;
+use data_processor::{
+    chart_updater::{
+        execute_chart_pipeline, line_chart_key, migrate_line_chart_data, update_chart,
+    },
+    charts::{self, chart::ChartType, chart_config::ChartConfig, Chart},
+    date_util::tms2000_to_timestamp,
+    submit_data_schema::SubmitDataChartSchema,
+    util::redis::RedisConnection,
+};
+use redis::AsyncCommands;
+use serde_json::{json, Value};
 
 #[tokio::test]
 async fn test_find_by_id() {
@@ -7,7 +19,7 @@ async fn test_find_by_id() {
     let mut con = test_environment.redis_connection().await;
 
     let chart = charts::find_by_id(&mut con, 1).await;
-    assert_eq!(chart.unwrap().unwrap().id_custom, "servers");
+    assert_eq!(chart.unwrap().unwrap().unwrap().id_custom, "servers");
 }
 
 #[tokio::test]
@@ -15,16 +27,386 @@ async fn test_find_by_ids() {
     let test_environment = TestEnvironment::with_data().await;
     let mut con = test_environment.redis_connection().await;
 
-    let charts: std::collections::HashMap<u64, Option<charts::Chart>> =
-        charts::find_by_ids(&mut con, vec![1, 2]).await.unwrap();
+    let charts = charts::find_by_ids(&mut con, vec![1, 2]).await.unwrap();
+
+    let id_custom = |id: u64| {
+        let chart = charts.get(&id).unwrap().as_ref().unwrap();
+        chart.as_ref().unwrap().id_custom.clone()
+    };
+    assert_eq!(id_custom(1), "servers");
+    assert_eq!(id_custom(2), "players");
+}
+
+#[tokio::test]
+async fn test_find_by_id_with_invalid_config() {
+    let test_environment = TestEnvironment::with_data().await;
+    let mut con = test_environment.redis_connection().await;
+
+    let _: () = con
+        .hset("charts:1", "data", r#"{ "filter": "invalid" }"#)
+        .await
+        .unwrap();
+
+    let chart = charts::find_by_id(&mut con, 1).await.unwrap().unwrap();
+    let error = chart.unwrap_err();
+    assert_eq!(error.id_custom, "servers");
+    assert!(
+        error.message.starts_with("invalid 'data'"),
+        "{}",
+        error.message
+    );
+}
 
+#[tokio::test]
+async fn test_update_advanced_map_chart() {
+    let test_environment = TestEnvironment::empty().await;
+    let mut con = test_environment.redis_connection().await;
+
+    let chart = get_chart(ChartType::AdvancedMap);
+    let tms2000 = 1337;
+
+    submit_chart_data(
+        &mut con,
+        &chart,
+        json!({
+            "values": {
+                "DE": 3,
+                "AUTO": 2,
+                "XX": 5
+            }
+        }),
+        tms2000,
+    )
+    .await;
+
+    let key = format!("data:{{{}}}.{}.{}", chart.service_id, chart.id, tms2000);
+    let values: Vec<(String, u16)> = con.zrange_withscores(&key, 0, -1).await.unwrap();
     assert_eq!(
-        charts.get(&1).unwrap().as_ref().unwrap().id_custom.clone(),
-        "servers"
+        values,
+        vec![(String::from("US"), 2), (String::from("DE"), 3)],
+        "Invalid ISO codes must be ignored and AUTO must be resolved"
     );
+}
+
+#[tokio::test]
+async fn test_update_simple_bar_chart() {
+    let test_environment = TestEnvironment::empty().await;
+    let mut con = test_environment.redis_connection().await;
+
+    let chart = get_chart(ChartType::SimpleBar);
+    let tms2000 = 1337;
 
+    // The Metrics classes send the value wrapped in an array
+    submit_chart_data(
+        &mut con,
+        &chart,
+        json!({ "values": { "Bar A": [3], "Bar B": [1] } }),
+        tms2000,
+    )
+    .await;
+    submit_chart_data(
+        &mut con,
+        &chart,
+        json!({ "values": { "Bar A": 2 } }),
+        tms2000,
+    )
+    .await;
+
+    let key = format!("data:{{{}}}.{}.{}", chart.service_id, chart.id, tms2000);
+    let bars: Vec<(String, u16)> = con.zrange_withscores(&key, 0, -1).await.unwrap();
     assert_eq!(
-        charts.get(&2).unwrap().as_ref().unwrap().id_custom.clone(),
-        "players"
+        bars,
+        vec![(String::from("Bar B"), 1), (String::from("Bar A"), 5)]
     );
+
+    let values: HashMap<u16, u16> = con.hgetall(format!("{}.Bar A", key)).await.unwrap();
+    assert_eq!(values, HashMap::from([(0, 5)]));
+}
+
+#[tokio::test]
+async fn test_update_advanced_bar_chart() {
+    let test_environment = TestEnvironment::empty().await;
+    let mut con = test_environment.redis_connection().await;
+
+    let chart = get_chart(ChartType::AdvancedBar);
+    let tms2000 = 1337;
+
+    submit_chart_data(
+        &mut con,
+        &chart,
+        json!({ "values": { "Bar A": [1, 2, 3] } }),
+        tms2000,
+    )
+    .await;
+    submit_chart_data(
+        &mut con,
+        &chart,
+        json!({ "values": { "Bar A": [4, 5, 6] } }),
+        tms2000,
+    )
+    .await;
+
+    let key = format!("data:{{{}}}.{}.{}", chart.service_id, chart.id, tms2000);
+    let bars: Vec<(String, u16)> = con.zrange_withscores(&key, 0, -1).await.unwrap();
+    assert_eq!(bars, vec![(String::from("Bar A"), 21)]);
+
+    let values: HashMap<u16, u16> = con.hgetall(format!("{}.Bar A", key)).await.unwrap();
+    assert_eq!(values, HashMap::from([(0, 5), (1, 7), (2, 9)]));
+
+    let ttl: i64 = con.ttl(format!("{}.Bar A", key)).await.unwrap();
+    assert!(ttl > 0, "Bar data must expire");
+}
+
+#[tokio::test]
+async fn test_update_multi_line_chart() {
+    let test_environment = TestEnvironment::empty().await;
+    let mut con = test_environment.redis_connection().await;
+
+    let mut chart = get_chart(ChartType::MultiLineChart);
+    chart.data =
+        ChartConfig::from_value(&ChartType::MultiLineChart, json!({ "maxLines": 2 })).unwrap();
+    let tms2000 = 1337;
+
+    submit_chart_data(
+        &mut con,
+        &chart,
+        json!({ "values": { "players": 5, "entities": 100 } }),
+        tms2000,
+    )
+    .await;
+    // The third line exceeds the limit of the chart
+    submit_chart_data(
+        &mut con,
+        &chart,
+        json!({ "values": { "players": 3, "chunks": 50 } }),
+        tms2000,
+    )
+    .await;
+
+    let timestamp = tms2000_to_timestamp(tms2000);
+    let players: i64 = con
+        .hget(
+            line_chart_key(chart.service_id, chart.id, "players"),
+            timestamp,
+        )
+        .await
+        .unwrap();
+    assert_eq!(players, 8);
+    let entities: i64 = con
+        .hget(
+            line_chart_key(chart.service_id, chart.id, "entities"),
+            timestamp,
+        )
+        .await
+        .unwrap();
+    assert_eq!(entities, 100);
+    let chunks: bool = con
+        .exists(line_chart_key(chart.service_id, chart.id, "chunks"))
+        .await
+        .unwrap();
+    assert!(!chunks, "Lines beyond the limit must be dropped");
+}
+
+#[tokio::test]
+async fn test_update_single_line_chart_with_large_values() {
+    let test_environment = TestEnvironment::empty().await;
+    let mut con = test_environment.redis_connection().await;
+
+    let chart = get_chart(ChartType::SingleLineChart);
+    let tms2000 = 1337;
+    let key = line_chart_key(chart.service_id, chart.id, "1");
+    let timestamp = tms2000_to_timestamp(tms2000);
+
+    // Values beyond the i16 range must be accepted
+    submit_chart_data(&mut con, &chart, json!({ "value": 100_000 }), tms2000).await;
+    let value: i64 = con.hget(&key, timestamp).await.unwrap();
+    assert_eq!(value, 100_000);
+
+    // The sum must saturate instead of overflowing
+    submit_chart_data(&mut con, &chart, json!({ "value": i64::MAX }), tms2000).await;
+    let value: i64 = con.hget(&key, timestamp).await.unwrap();
+    assert_eq!(value, i64::MAX);
+
+    submit_chart_data(&mut con, &chart, json!({ "value": -5 }), tms2000).await;
+    let value: i64 = con.hget(&key, timestamp).await.unwrap();
+    assert_eq!(value, i64::MAX - 5);
+}
+
+#[tokio::test]
+async fn test_update_line_chart_without_cached_script() {
+    let test_environment = TestEnvironment::empty().await;
+    let mut con = test_environment.redis_connection().await;
+
+    let chart = get_chart(ChartType::SingleLineChart);
+    let pie_chart = Chart {
+        id: 2,
+        id_custom: String::from("my_pie"),
+        ..get_chart(ChartType::SimplePie)
+    };
+    let tms2000 = 1337;
+
+    let _: () = redis::cmd("SCRIPT")
+        .arg("FLUSH")
+        .query_async(&mut con)
+        .await
+        .unwrap();
+
+    let mut pipeline = redis::pipe();
+    for (chart, data) in [
+        (&chart, json!({ "value": 5 })),
+        (&pie_chart, json!({ "value": "My value" })),
+    ] {
+        update_chart(
+            chart,
+            &SubmitDataChartSchema {
+                chart_id: chart.id_custom.clone(),
+                data,
+                trusted: false,
+            },
+            tms2000,
+            None,
+            &mut pipeline,
+        )
+        .unwrap();
+    }
+    execute_chart_pipeline(&pipeline, &mut con).await.unwrap();
+
+    let value: i64 = con
+        .hget(
+            line_chart_key(chart.service_id, chart.id, "1"),
+            tms2000_to_timestamp(tms2000),
+        )
+        .await
+        .unwrap();
+    assert_eq!(value, 5);
+    // The other commands must not be repeated
+    let pie_value: u16 = con
+        .zscore(
+            format!(
+                "data:{{{}}}.{}.{}",
+                pie_chart.service_id, pie_chart.id, tms2000
+            ),
+            "My value",
+        )
+        .await
+        .unwrap();
+    assert_eq!(pie_value, 1);
+}
+
+#[tokio::test]
+async fn test_migrate_line_chart_data() {
+    let test_environment = TestEnvironment::empty().await;
+    let mut con = test_environment.redis_connection().await;
+
+    let chart = get_chart(ChartType::SingleLineChart);
+    let tms2000 = 1337;
+    let timestamp = tms2000_to_timestamp(tms2000);
+
+    // Data written with the old key scheme ...
+    let _: () = con
+        .hset(format!("data:{{{}}}.1", chart.id), timestamp, 5)
+        .await
+        .unwrap();
+    // ... and data written after the new key scheme was introduced
+    submit_chart_data(&mut con, &chart, json!({ "value": 3 }), tms2000).await;
+
+    migrate_line_chart_data(&chart, &mut con).await.unwrap();
+
+    let value: i64 = con
+        .hget(line_chart_key(chart.service_id, chart.id, "1"), timestamp)
+        .await
+        .unwrap();
+    assert_eq!(value, 8);
+    let old_exists: bool = con
+        .exists(format!("data:{{{}}}.1", chart.id))
+        .await
+        .unwrap();
+    assert!(!old_exists, "The old key must be removed");
+}
+
+#[tokio::test]
+async fn test_migrate_line_chart_data_twice() {
+    let test_environment = TestEnvironment::empty().await;
+    let mut con = test_environment.redis_connection().await;
+
+    let chart = get_chart(ChartType::SingleLineChart);
+    let tms2000 = 1337;
+    let timestamp = tms2000_to_timestamp(tms2000);
+    let old_key = format!("data:{{{}}}.1", chart.id);
+    let snapshot_key = format!("migrating:{{{}}}.1", chart.id);
+
+    let _: () = con.hset(&old_key, timestamp, 5).await.unwrap();
+    migrate_line_chart_data(&chart, &mut con).await.unwrap();
+
+    // Simulate a migration that was interrupted before the snapshot was deleted
+    let _: () = con.hset(&snapshot_key, timestamp, 5).await.unwrap();
+    migrate_line_chart_data(&chart, &mut con).await.unwrap();
+
+    let value: i64 = con
+        .hget(line_chart_key(chart.service_id, chart.id, "1"), timestamp)
+        .await
+        .unwrap();
+    assert_eq!(value, 5);
+    for key in [&old_key, &snapshot_key] {
+        let exists: bool = con.exists(key).await.unwrap();
+        assert!(!exists, "{} must be removed", key);
+    }
+}
+
+#[tokio::test]
+async fn test_migrate_line_chart_data_with_concurrent_writes() {
+    let test_environment = TestEnvironment::empty().await;
+    let mut con = test_environment.redis_connection().await;
+
+    let chart = get_chart(ChartType::SingleLineChart);
+    let tms2000 = 1337;
+    let timestamp = tms2000_to_timestamp(tms2000);
+    let old_key = format!("data:{{{}}}.1", chart.id);
+
+    let _: () = con.hset(&old_key, timestamp, 5).await.unwrap();
+    migrate_line_chart_data(&chart, &mut con).await.unwrap();
+
+    // A data processor that still uses the old key scheme writes more data
+    let _: () = con.hincr(&old_key, timestamp, 3).await.unwrap();
+    let _: () = con.hincr(&old_key, timestamp + 1000, 2).await.unwrap();
+    migrate_line_chart_data(&chart, &mut con).await.unwrap();
+
+    let key = line_chart_key(chart.service_id, chart.id, "1");
+    let value: i64 = con.hget(&key, timestamp).await.unwrap();
+    assert_eq!(value, 8);
+    let value: i64 = con.hget(&key, timestamp + 1000).await.unwrap();
+    assert_eq!(value, 2);
+    let old_exists: bool = con.exists(&old_key).await.unwrap();
+    assert!(!old_exists, "The old key must be removed");
+}
+
+fn get_chart(r#type: ChartType) -> Chart {
+    let data = ChartConfig::from_value(&r#type, Value::Null).unwrap();
+    Chart {
+        id: 1,
+        id_custom: String::from("my_chart"),
+        r#type,
+        position: 0,
+        title: String::from("My fancy chart"),
+        default: false,
+        data,
+        service_id: 1,
+    }
+}
+
+async fn submit_chart_data(con: &mut RedisConnection, chart: &Chart, data: Value, tms2000: i64) {
+    let mut pipeline = redis::pipe();
+    update_chart(
+        chart,
+        &SubmitDataChartSchema {
+            chart_id: chart.id_custom.clone(),
+            data,
+            trusted: false,
+        },
+        tms2000,
+        Some("US"),
+        &mut pipeline,
+    )
+    .unwrap();
+    execute_chart_pipeline(&pipeline, con).await.unwrap();
 }
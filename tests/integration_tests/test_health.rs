@@ -0,0 +1,34 @@
+use actix_web::{test, web, App};
+use data_processor::{health_endpoint, ready_endpoint};
+use serde_json::Value;
+
+use crate::helper::test_environment::TestEnvironment;
+
+#[actix_web::test]
+async fn test_health() {
+    let app = test::init_service(App::new().service(health_endpoint)).await;
+    let req = test::TestRequest::get().uri("/health").to_request();
+
+    let resp = test::call_service(&app, req).await;
+    assert!(resp.status().is_success());
+}
+
+#[actix_web::test]
+async fn test_ready_checks_redis() {
+    let test_environment = TestEnvironment::empty().await;
+
+    let redis_pool = test_environment.redis_pool();
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(redis_pool.clone()))
+            .service(ready_endpoint),
+    )
+    .await;
+    let req = test::TestRequest::get().uri("/ready").to_request();
+
+    let resp = test::call_service(&app, req).await;
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["checks"]["redis"], "ok");
+    assert_eq!(body["checks"]["server_software_rules"], "ok");
+}
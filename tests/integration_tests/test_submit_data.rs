@@ -1,10 +1,15 @@
 use std::net::{IpAddr, Ipv4Addr, SocketAddr};
 
 use actix_web::{http::header::ContentType, test, web, App};
-use data_processor::submit_data;
-use serde_json::json;
+use data_processor::{
+    charts::{chart::ChartType, chart_config::ChartConfig, Chart},
+    date_util::date_to_tms2000,
+    submit_data,
+};
+use redis::AsyncCommands;
+use serde_json::{json, Value};
 
-use crate::helper::test_environment::TestEnvironment;
+use crate::helper::test_environment::{get_line_chart, TestEnvironment};
 
 #[actix_web::test]
 async fn test_submit_data() {
@@ -15,6 +20,7 @@ async fn test_submit_data() {
     let app = test::init_service(
         App::new()
             .app_data(web::Data::new(redis_pool.clone()))
+            .app_data(web::Data::new(test_environment.config()))
             .service(submit_data),
     )
     .await;
@@ -58,3 +64,150 @@ async fn test_submit_data() {
     let body = test::read_body(resp).await;
     assert_eq!(body, "");
 }
+
+#[actix_web::test]
+async fn test_submit_data_debug_mode() {
+    let test_environment = TestEnvironment::with_data().await;
+
+    let redis_pool = test_environment.redis_pool();
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(redis_pool.clone()))
+            .app_data(web::Data::new(test_environment.config()))
+            .service(submit_data),
+    )
+    .await;
+    let req = test::TestRequest::post()
+        .uri("/bukkit?debug=true")
+        .peer_addr(SocketAddr::new(IpAddr::V4(Ipv4Addr::new(1, 1, 1, 1)), 1111))
+        .insert_header(ContentType::json())
+        .set_payload(
+            json!({
+                "service": {
+                    "id": 3,
+                    "customCharts": [
+                    {
+                        "chartId": "unknown_chart",
+                        "data": {
+                        "value": "My value"
+                        }
+                    }
+                    ]
+                },
+                "serverUUID": "7386d410-f71e-447c-b356-ee809c7db098",
+                "metricsVersion": "3.0.2"
+            })
+            .to_string(),
+        )
+        .to_request();
+
+    let resp = test::call_service(&app, req).await;
+    assert!(resp.status().is_success());
+
+    let report: Value = test::read_body_json(resp).await;
+    let chart_report = report["charts"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|c| c["chartId"] == "unknown_chart")
+        .expect("Report should contain the custom chart");
+    assert_eq!(chart_report["status"], "unknown");
+
+    // The request does not contain the online mode, so its default chart has no value
+    let chart_report = report["charts"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|c| c["chartId"] == "onlineMode")
+        .expect("Report should contain the default chart");
+    assert_eq!(chart_report["status"], "noValue");
+}
+
+#[actix_web::test]
+async fn test_submit_data_with_invalid_chart() {
+    let mut test_environment = TestEnvironment::with_data().await;
+
+    let valid_chart = Chart {
+        id: 32,
+        id_custom: String::from("valid_chart"),
+        r#type: ChartType::SimplePie,
+        position: 0,
+        title: String::from("My fancy pie"),
+        default: false,
+        data: ChartConfig::from_value(&ChartType::SimplePie, Value::Null).unwrap(),
+        service_id: 3,
+    };
+    test_environment.add_chart(valid_chart).await;
+    test_environment
+        .add_chart(get_line_chart(3, 33, String::from("invalid_chart")))
+        .await;
+
+    // Corrupt the config of the line chart
+    let mut con = test_environment.redis_connection().await;
+    let _: () = con
+        .hset("charts:33", "data", r#"{ "filter": "invalid" }"#)
+        .await
+        .unwrap();
+
+    let redis_pool = test_environment.redis_pool();
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(redis_pool.clone()))
+            .app_data(web::Data::new(test_environment.config()))
+            .service(submit_data),
+    )
+    .await;
+    let req = test::TestRequest::post()
+        .uri("/bukkit?debug=true")
+        .peer_addr(SocketAddr::new(IpAddr::V4(Ipv4Addr::new(1, 1, 1, 1)), 1111))
+        .insert_header(ContentType::json())
+        .set_payload(
+            json!({
+                "service": {
+                    "id": 3,
+                    "customCharts": [
+                    {
+                        "chartId": "valid_chart",
+                        "data": {
+                        "value": "My value"
+                        }
+                    },
+                    {
+                        "chartId": "invalid_chart",
+                        "data": {
+                        "value": 5
+                        }
+                    }
+                    ]
+                },
+                "serverUUID": "7386d410-f71e-447c-b356-ee809c7db098",
+                "metricsVersion": "3.0.2"
+            })
+            .to_string(),
+        )
+        .to_request();
+
+    let resp = test::call_service(&app, req).await;
+    assert!(resp.status().is_success());
+
+    let report: Value = test::read_body_json(resp).await;
+    let status = |chart_id: &str| {
+        report["charts"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|c| c["chartId"] == chart_id)
+            .map(|c| c["status"].clone())
+    };
+    assert_eq!(status("valid_chart"), Some(json!("accepted")));
+    assert_eq!(status("invalid_chart"), Some(json!("invalidConfig")));
+
+    let tms2000 = date_to_tms2000(chrono::Utc::now());
+    let value: Option<u16> = con
+        .zscore(format!("data:{{3}}.32.{}", tms2000), "My value")
+        .await
+        .unwrap();
+    assert_eq!(value, Some(1));
+}